@@ -71,6 +71,11 @@ fn main() {
     let group = WaterscapeGroup::new("secret-council", &alice, members);
     println!("Created group: {}", group.name());
     println!("Members: {}", group.members().len());
+
+    // Alice hands the group key to each member in an encrypted envelope
+    let envelopes = group.envelopes(&alice).expect("Envelope creation failed");
+    let bob_group = WaterscapeGroup::from_envelope(&bob, &envelopes[0]).expect("Bob could not join");
+    println!("Bob joined with an envelope of {} bytes", envelopes[0].to_bytes().unwrap().len());
     println!();
 
     let group_cover = "Team meeting notes: discussed project timeline and resource allocation.";
@@ -81,7 +86,7 @@ fn main() {
         .encode(&alice, group_cover, group_secret)
         .expect("Group encoding failed");
 
-    let group_decoded = group.decode(&group_encoded).expect("Group decoding failed");
+    let group_decoded = bob_group.decode(&group_encoded).expect("Group decoding failed");
 
     println!("Group message decoded: \"{}\"", group_decoded);
     assert_eq!(group_decoded, group_secret);
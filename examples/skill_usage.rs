@@ -54,6 +54,7 @@ fn main() {
         recipient_name: "bob".to_string(),
         cover_text: cover_text.to_string(),
         secret_message: secret.to_string(),
+        bind_cover: true,
     });
 
     let encoded_text = match response {
@@ -85,6 +86,7 @@ fn main() {
     match response {
         SkillResponse::Success { result } => {
             println!("  Decoded: \"{}\"", result["secret_message"]);
+            println!("  Cover text: {}", result["cover"]);
         }
         SkillResponse::Error { message, code } => {
             panic!("Decoding failed: {} ({})", message, code);
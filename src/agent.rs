@@ -4,14 +4,24 @@
 //! - A unique identifier (name)
 //! - An Ed25519 signing key pair for authentication
 //! - An X25519 key pair for key exchange
+//!
+//! Both key pairs can be exported together as an [`AgentBackup`], or derived
+//! deterministically from a single 32-byte master seed.
 
 use serde::{Deserialize, Serialize};
+use zeroize::{Zeroize, ZeroizeOnDrop};
 
-use crate::crypto::{KeyExchangePair, SigningKeyPair};
+use crate::crypto::{self, KeyExchangePair, SigningKeyPair};
+use crate::error::WaterscapeError;
+use crate::protocol::unix_timestamp_secs;
 use crate::Result;
 
+pub const BACKUP_VERSION: u8 = 1;
+const CONTEXT_SEED_SIGNING: &[u8] = b"waterscape-v1-seed-signing";
+const CONTEXT_SEED_EXCHANGE: &[u8] = b"waterscape-v1-seed-exchange";
+
 /// Public identity of an agent (can be shared freely)
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct PublicIdentity {
     pub name: String,
     pub signing_key: [u8; 32],
@@ -25,11 +35,48 @@ impl PublicIdentity {
     }
 }
 
+/// Complete private key material of an agent (keep secret)
+///
+/// Serialized as versioned JSON with hex-encoded keys. Key bytes are zeroized on drop.
+#[derive(Serialize, Deserialize, Zeroize, ZeroizeOnDrop)]
+pub struct AgentBackup {
+    pub version: u8,
+    pub name: String,
+    #[serde(with = "hex::serde")]
+    pub signing_key: [u8; 32],
+    #[serde(with = "hex::serde")]
+    pub exchange_key: [u8; 32],
+    /// Unix timestamp (seconds) of when the agent was created
+    #[serde(default)]
+    pub created_at: u64,
+}
+
+impl AgentBackup {
+    /// Serialize to bytes
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        serde_json::to_vec(self).map_err(|e| WaterscapeError::Serialization(e.to_string()))
+    }
+
+    /// Deserialize from bytes
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let backup: Self = serde_json::from_slice(bytes)
+            .map_err(|e| WaterscapeError::Serialization(e.to_string()))?;
+        if backup.version != BACKUP_VERSION {
+            return Err(WaterscapeError::VersionMismatch {
+                expected: BACKUP_VERSION,
+                got: backup.version,
+            });
+        }
+        Ok(backup)
+    }
+}
+
 /// Full agent with private keys
 pub struct Agent {
     name: String,
     signing_keypair: SigningKeyPair,
     exchange_keypair: KeyExchangePair,
+    created_at: u64,
 }
 
 impl Agent {
@@ -39,6 +86,7 @@ impl Agent {
             name: name.to_string(),
             signing_keypair: SigningKeyPair::generate(),
             exchange_keypair: KeyExchangePair::generate(),
+            created_at: unix_timestamp_secs(),
         }
     }
 
@@ -46,22 +94,52 @@ impl Agent {
     pub fn from_keys(
         name: &str,
         signing_key_bytes: &[u8; 32],
+        exchange_key_bytes: &[u8; 32],
     ) -> Result<Self> {
         let signing_keypair = SigningKeyPair::from_bytes(signing_key_bytes)?;
-        let exchange_keypair = KeyExchangePair::generate();
-        
+        let exchange_keypair = KeyExchangePair::from_bytes(exchange_key_bytes);
+
         Ok(Self {
             name: name.to_string(),
             signing_keypair,
             exchange_keypair,
+            created_at: unix_timestamp_secs(),
         })
     }
 
+    /// Deterministically derive both key pairs from a 32-byte master seed
+    pub fn from_seed(name: &str, seed: &[u8; 32]) -> Result<Self> {
+        let mut signing_key = crypto::derive_from_seed(seed, CONTEXT_SEED_SIGNING);
+        let mut exchange_key = crypto::derive_from_seed(seed, CONTEXT_SEED_EXCHANGE);
+        let agent = Self::from_keys(name, &signing_key, &exchange_key);
+        signing_key.zeroize();
+        exchange_key.zeroize();
+        agent
+    }
+
+    /// Restore agent from a full backup
+    pub fn from_backup(backup: &AgentBackup) -> Result<Self> {
+        if backup.version != BACKUP_VERSION {
+            return Err(WaterscapeError::VersionMismatch {
+                expected: BACKUP_VERSION,
+                got: backup.version,
+            });
+        }
+        let mut agent = Self::from_keys(&backup.name, &backup.signing_key, &backup.exchange_key)?;
+        agent.created_at = backup.created_at;
+        Ok(agent)
+    }
+
     /// Get agent name
     pub fn name(&self) -> &str {
         &self.name
     }
 
+    /// Get creation time (unix timestamp in seconds)
+    pub fn created_at(&self) -> u64 {
+        self.created_at
+    }
+
     /// Get public identity (safe to share)
     pub fn public_identity(&self) -> PublicIdentity {
         PublicIdentity {
@@ -86,6 +164,22 @@ impl Agent {
         self.signing_keypair.signing_key_bytes()
     }
 
+    /// Export private exchange key (for backup)
+    pub fn export_exchange_key(&self) -> [u8; 32] {
+        self.exchange_keypair.secret_key_bytes()
+    }
+
+    /// Export name and both private keys (for backup)
+    pub fn export_backup(&self) -> AgentBackup {
+        AgentBackup {
+            version: BACKUP_VERSION,
+            name: self.name.clone(),
+            signing_key: self.export_signing_key(),
+            exchange_key: self.export_exchange_key(),
+            created_at: self.created_at,
+        }
+    }
+
     /// Sign arbitrary data
     pub fn sign(&self, data: &[u8]) -> Vec<u8> {
         self.signing_keypair.sign(data).to_bytes().to_vec()
@@ -118,6 +212,11 @@ impl AgentRegistry {
         self.agents.values().find(|a| a.fingerprint() == fingerprint)
     }
 
+    /// Look up agent by signing public key
+    pub fn get_by_signing_key(&self, signing_key: &[u8; 32]) -> Option<&PublicIdentity> {
+        self.agents.values().find(|a| &a.signing_key == signing_key)
+    }
+
     /// List all known agents
     pub fn list(&self) -> Vec<&PublicIdentity> {
         self.agents.values().collect()
@@ -143,6 +242,45 @@ mod tests {
         assert!(!identity.fingerprint().is_empty());
     }
 
+    #[test]
+    fn test_backup_restore() {
+        let agent = Agent::new("backup-agent");
+        let bytes = agent.export_backup().to_bytes().unwrap();
+
+        let restored = Agent::from_backup(&AgentBackup::from_bytes(&bytes).unwrap()).unwrap();
+        let original_identity = agent.public_identity();
+        let restored_identity = restored.public_identity();
+
+        assert_eq!(restored_identity.name, original_identity.name);
+        assert_eq!(restored_identity.signing_key, original_identity.signing_key);
+        assert_eq!(restored_identity.exchange_key, original_identity.exchange_key);
+        assert_eq!(restored.created_at(), agent.created_at());
+    }
+
+    #[test]
+    fn test_backup_version_mismatch() {
+        let mut backup = Agent::new("agent").export_backup();
+        backup.version = BACKUP_VERSION + 1;
+        let bytes = backup.to_bytes().unwrap();
+
+        assert!(matches!(
+            AgentBackup::from_bytes(&bytes),
+            Err(WaterscapeError::VersionMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn test_from_seed_is_deterministic() {
+        let seed = [7u8; 32];
+        let first = Agent::from_seed("seeded", &seed).unwrap().public_identity();
+        let second = Agent::from_seed("seeded", &seed).unwrap().public_identity();
+        let other = Agent::from_seed("seeded", &[8u8; 32]).unwrap().public_identity();
+
+        assert_eq!(first.signing_key, second.signing_key);
+        assert_eq!(first.exchange_key, second.exchange_key);
+        assert_ne!(first.exchange_key, other.exchange_key);
+    }
+
     #[test]
     fn test_agent_registry() {
         let mut registry = AgentRegistry::new();
@@ -6,14 +6,14 @@
 //! - HKDF for key derivation
 
 use chacha20poly1305::{
-    aead::{Aead, KeyInit},
+    aead::{Aead, KeyInit, Payload},
     ChaCha20Poly1305, Nonce,
 };
 use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
 use hkdf::Hkdf;
 use rand::rngs::OsRng;
 use sha2::Sha256;
-use x25519_dalek::{PublicKey as X25519PublicKey, StaticSecret};
+use x25519_dalek::{EphemeralSecret, PublicKey as X25519PublicKey, StaticSecret};
 use zeroize::Zeroize;
 
 use crate::error::WaterscapeError;
@@ -22,6 +22,8 @@ use crate::Result;
 pub const NONCE_SIZE: usize = 12;
 pub const KEY_SIZE: usize = 32;
 pub const SIGNATURE_SIZE: usize = 64;
+/// Poly1305 authentication tag appended to every ciphertext
+pub const TAG_SIZE: usize = 16;
 
 /// Key pair for X25519 key exchange
 pub struct KeyExchangePair {
@@ -36,6 +38,12 @@ impl KeyExchangePair {
         Self { secret, public }
     }
 
+    pub fn from_bytes(bytes: &[u8; 32]) -> Self {
+        let secret = StaticSecret::from(*bytes);
+        let public = X25519PublicKey::from(&secret);
+        Self { secret, public }
+    }
+
     pub fn public_key(&self) -> &X25519PublicKey {
         &self.public
     }
@@ -44,6 +52,10 @@ impl KeyExchangePair {
         self.public.to_bytes()
     }
 
+    pub fn secret_key_bytes(&self) -> [u8; 32] {
+        self.secret.to_bytes()
+    }
+
     /// Perform Diffie-Hellman key exchange
     pub fn diffie_hellman(&self, their_public: &X25519PublicKey) -> SharedSecret {
         let shared = self.secret.diffie_hellman(their_public);
@@ -51,6 +63,32 @@ impl KeyExchangePair {
     }
 }
 
+/// Single-use X25519 key pair, generated fresh for every message
+///
+/// The secret is consumed by the key exchange and zeroized on drop.
+pub struct EphemeralKeyPair {
+    secret: EphemeralSecret,
+    public: X25519PublicKey,
+}
+
+impl EphemeralKeyPair {
+    pub fn generate() -> Self {
+        let secret = EphemeralSecret::random_from_rng(OsRng);
+        let public = X25519PublicKey::from(&secret);
+        Self { secret, public }
+    }
+
+    pub fn public_key_bytes(&self) -> [u8; 32] {
+        self.public.to_bytes()
+    }
+
+    /// Perform Diffie-Hellman key exchange, consuming the ephemeral secret
+    pub fn diffie_hellman(self, their_public: &X25519PublicKey) -> SharedSecret {
+        let shared = self.secret.diffie_hellman(their_public);
+        SharedSecret(shared.to_bytes())
+    }
+}
+
 /// Shared secret from key exchange
 pub struct SharedSecret([u8; 32]);
 
@@ -63,6 +101,20 @@ impl SharedSecret {
             .expect("HKDF expand should not fail with valid length");
         key
     }
+
+    /// Derive encryption key using HKDF over this secret concatenated with another
+    pub fn derive_key_with(&self, other: &SharedSecret, context: &[u8]) -> [u8; KEY_SIZE] {
+        let mut ikm = [0u8; 64];
+        ikm[..32].copy_from_slice(&self.0);
+        ikm[32..].copy_from_slice(&other.0);
+
+        let hk = Hkdf::<Sha256>::new(None, &ikm);
+        let mut key = [0u8; KEY_SIZE];
+        hk.expand(context, &mut key)
+            .expect("HKDF expand should not fail with valid length");
+        ikm.zeroize();
+        key
+    }
 }
 
 impl Drop for SharedSecret {
@@ -134,11 +186,21 @@ pub fn verify_signature(
 
 /// Encrypt data using ChaCha20-Poly1305
 pub fn encrypt(key: &[u8; KEY_SIZE], nonce: &[u8; NONCE_SIZE], plaintext: &[u8]) -> Result<Vec<u8>> {
+    encrypt_with_aad(key, nonce, plaintext, &[])
+}
+
+/// Encrypt data using ChaCha20-Poly1305, authenticating `aad` alongside it
+pub fn encrypt_with_aad(
+    key: &[u8; KEY_SIZE],
+    nonce: &[u8; NONCE_SIZE],
+    plaintext: &[u8],
+    aad: &[u8],
+) -> Result<Vec<u8>> {
     let cipher = ChaCha20Poly1305::new_from_slice(key)
         .map_err(|_| WaterscapeError::Crypto("Invalid key length".into()))?;
     let nonce = Nonce::from_slice(nonce);
     cipher
-        .encrypt(nonce, plaintext)
+        .encrypt(nonce, Payload { msg: plaintext, aad })
         .map_err(|_| WaterscapeError::Crypto("Encryption failed".into()))
 }
 
@@ -147,15 +209,41 @@ pub fn decrypt(
     key: &[u8; KEY_SIZE],
     nonce: &[u8; NONCE_SIZE],
     ciphertext: &[u8],
+) -> Result<Vec<u8>> {
+    decrypt_with_aad(key, nonce, ciphertext, &[])
+}
+
+/// Decrypt data using ChaCha20-Poly1305, verifying the associated data `aad`
+pub fn decrypt_with_aad(
+    key: &[u8; KEY_SIZE],
+    nonce: &[u8; NONCE_SIZE],
+    ciphertext: &[u8],
+    aad: &[u8],
 ) -> Result<Vec<u8>> {
     let cipher = ChaCha20Poly1305::new_from_slice(key)
         .map_err(|_| WaterscapeError::Crypto("Invalid key length".into()))?;
     let nonce = Nonce::from_slice(nonce);
     cipher
-        .decrypt(nonce, ciphertext)
+        .decrypt(nonce, Payload { msg: ciphertext, aad })
         .map_err(|_| WaterscapeError::AuthenticationFailed)
 }
 
+/// Derive a 32-byte key from a master seed using HKDF
+pub fn derive_from_seed(seed: &[u8; 32], context: &[u8]) -> [u8; KEY_SIZE] {
+    let hk = Hkdf::<Sha256>::new(None, seed);
+    let mut key = [0u8; KEY_SIZE];
+    hk.expand(context, &mut key)
+        .expect("HKDF expand should not fail with valid length");
+    key
+}
+
+/// Generate a random symmetric key
+pub fn generate_key() -> [u8; KEY_SIZE] {
+    let mut key = [0u8; KEY_SIZE];
+    rand::RngCore::fill_bytes(&mut OsRng, &mut key);
+    key
+}
+
 /// Generate a random nonce
 pub fn generate_nonce() -> [u8; NONCE_SIZE] {
     let mut nonce = [0u8; NONCE_SIZE];
@@ -181,6 +269,26 @@ mod tests {
         assert_eq!(alice_key, bob_key);
     }
 
+    #[test]
+    fn test_ephemeral_key_exchange() {
+        let bob = KeyExchangePair::generate();
+        let ephemeral = EphemeralKeyPair::generate();
+        let ephemeral_public = X25519PublicKey::from(ephemeral.public_key_bytes());
+
+        let sender_key = ephemeral.diffie_hellman(bob.public_key()).derive_key(b"test");
+        let receiver_key = bob.diffie_hellman(&ephemeral_public).derive_key(b"test");
+
+        assert_eq!(sender_key, receiver_key);
+    }
+
+    #[test]
+    fn test_exchange_key_from_bytes() {
+        let original = KeyExchangePair::generate();
+        let restored = KeyExchangePair::from_bytes(&original.secret_key_bytes());
+
+        assert_eq!(original.public_key_bytes(), restored.public_key_bytes());
+    }
+
     #[test]
     fn test_encrypt_decrypt() {
         let key = [0u8; KEY_SIZE];
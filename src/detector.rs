@@ -0,0 +1,466 @@
+//! Steganalysis detector
+//!
+//! Scans untrusted text for the usual ways of hiding data in Unicode text, whether
+//! or not the data was produced by this crate:
+//! - runs of zero-width and other invisible formatting characters,
+//! - Unicode tag characters outside emoji flag sequences,
+//! - variation selectors that do not form a valid variation sequence,
+//! - Latin words mixed with Cyrillic or Greek homoglyphs,
+//! - trailing whitespace patterns and unusual space characters.
+//!
+//! Each finding carries the byte spans of the suspicious characters, an estimate of
+//! the hidden payload size and a confidence between 0 and 1. Payloads that decode
+//! as this crate's own format are reported with their exact size and confidence 1.
+
+use std::ops::Range;
+
+use serde::Serialize;
+
+use crate::stego::fec;
+use crate::stego::homoglyph;
+use crate::stego::tags;
+use crate::stego::variation;
+use crate::stego::whitespace;
+use crate::stego::{StegoCodec, ZeroWidthCodec};
+
+/// Hiding technique a finding belongs to
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Scheme {
+    ZeroWidth,
+    UnicodeTags,
+    VariationSelectors,
+    Homoglyphs,
+    Whitespace,
+}
+
+/// Evidence of one hiding scheme in the scanned text
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Finding {
+    pub scheme: Scheme,
+    /// Byte ranges of the suspicious characters, adjacent ones merged
+    pub spans: Vec<Range<usize>>,
+    /// Number of suspicious characters
+    pub symbols: usize,
+    /// Estimated size of the hidden payload in bytes
+    pub estimated_bytes: usize,
+    /// Likelihood that data is hidden, from 0 to 1
+    pub confidence: f32,
+    /// The payload decodes as a Waterscape carrier
+    pub waterscape_format: bool,
+}
+
+/// Result of scanning a text
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct DetectionReport {
+    pub findings: Vec<Finding>,
+}
+
+impl DetectionReport {
+    /// No scheme left any trace
+    pub fn is_clean(&self) -> bool {
+        self.findings.is_empty()
+    }
+
+    /// Highest confidence over all findings
+    pub fn confidence(&self) -> f32 {
+        self.findings.iter().map(|f| f.confidence).fold(0.0, f32::max)
+    }
+
+    /// Finding for a specific scheme
+    pub fn finding(&self, scheme: Scheme) -> Option<&Finding> {
+        self.findings.iter().find(|f| f.scheme == scheme)
+    }
+}
+
+/// Scan text for hidden data in every supported scheme
+pub fn scan(text: &str) -> DetectionReport {
+    let chars: Vec<(usize, char)> = text.char_indices().collect();
+    let findings = [
+        scan_zero_width(text, &chars),
+        scan_tags(text, &chars),
+        scan_variation_selectors(text, &chars),
+        scan_homoglyphs(text, &chars),
+        scan_whitespace(text, &chars),
+    ];
+    DetectionReport {
+        findings: findings.into_iter().flatten().collect(),
+    }
+}
+
+/// Invisible characters with no legitimate use outside a few scripts
+pub(crate) fn is_invisible(c: char) -> bool {
+    matches!(
+        c,
+        '\u{00AD}'
+            | '\u{034F}'
+            | '\u{061C}'
+            | '\u{115F}'
+            | '\u{1160}'
+            | '\u{17B4}'
+            | '\u{17B5}'
+            | '\u{180B}'..='\u{180F}'
+            | '\u{200B}'..='\u{200F}'
+            | '\u{2060}'..='\u{2064}'
+            | '\u{206A}'..='\u{206F}'
+            | '\u{3164}'
+            | '\u{FEFF}'
+            | '\u{FFA0}'
+            | '\u{1D173}'..='\u{1D17A}'
+    )
+}
+
+/// Characters that can start an emoji presentation or ZWJ sequence
+pub(crate) fn is_emoji_base(c: char) -> bool {
+    matches!(
+        c,
+        '#' | '*'
+            | '0'..='9'
+            | '\u{00A9}'
+            | '\u{00AE}'
+            | '\u{203C}'
+            | '\u{2049}'
+            | '\u{2122}'
+            | '\u{2139}'
+            | '\u{2194}'..='\u{21AA}'
+            | '\u{2300}'..='\u{23FF}'
+            | '\u{24C2}'
+            | '\u{25AA}'..='\u{25FE}'
+            | '\u{2600}'..='\u{27BF}'
+            | '\u{2934}'
+            | '\u{2935}'
+            | '\u{2B00}'..='\u{2BFF}'
+            | '\u{3030}'
+            | '\u{303D}'
+            | '\u{3297}'
+            | '\u{3299}'
+            | '\u{1F000}'..='\u{1FAFF}'
+    )
+}
+
+/// CJK ideographs, which have registered ideographic variation sequences
+fn is_ideograph(c: char) -> bool {
+    matches!(c, '\u{3400}'..='\u{4DBF}' | '\u{4E00}'..='\u{9FFF}' | '\u{F900}'..='\u{FAFF}' | '\u{20000}'..='\u{3134F}')
+}
+
+/// Mongolian letters, which take free variation selectors
+fn is_mongolian_letter(c: char) -> bool {
+    matches!(c, '\u{1820}'..='\u{18AA}')
+}
+
+/// Scripts where ZWJ/ZWNJ shape letters (Arabic, Syriac, Indic)
+fn uses_joiners(c: char) -> bool {
+    matches!(c, '\u{0600}'..='\u{08FF}' | '\u{0900}'..='\u{0DFF}')
+}
+
+/// Whether an invisible character at `i` is ordinary text rather than hidden data
+pub(crate) fn is_legitimate_invisible(chars: &[char], i: usize) -> bool {
+    let prev = i.checked_sub(1).and_then(|j| chars.get(j)).copied();
+    let next = chars.get(i + 1).copied();
+    match chars[i] {
+        // Byte order mark at the start of the text
+        '\u{FEFF}' => i == 0,
+        // Soft hyphen inside a word
+        '\u{00AD}' => prev.is_some_and(char::is_alphabetic) && next.is_some_and(char::is_alphabetic),
+        // Emoji ZWJ sequences (the base may carry a selector or skin tone first)
+        '\u{200D}' if next.is_some_and(is_emoji_base) && prev.is_some_and(|p| !p.is_whitespace()) => true,
+        '\u{200C}' | '\u{200D}' => prev.is_some_and(uses_joiners) && next.is_some_and(uses_joiners),
+        _ => false,
+    }
+}
+
+/// Whether the variation selector run `run` following `base` is a valid sequence
+pub(crate) fn is_legitimate_selector_run(base: Option<char>, run: &[char]) -> bool {
+    let (Some(base), [selector]) = (base, run) else {
+        return false;
+    };
+    match selector {
+        '\u{FE0E}' | '\u{FE0F}' => is_emoji_base(base),
+        '\u{E0100}'..='\u{E01EF}' => is_ideograph(base),
+        '\u{FE00}' => has_standardized_variant(base),
+        '\u{180B}'..='\u{180D}' | '\u{180F}' => is_mongolian_letter(base),
+        _ => false,
+    }
+}
+
+/// Bases of the standardized variation sequences with VS1 readers are likely to
+/// meet: the digit zero and mathematical symbols with variant glyphs. The rare
+/// script-specific and CJK compatibility sequences are treated as hidden data.
+fn has_standardized_variant(base: char) -> bool {
+    matches!(
+        base,
+        '0' | '\u{2205}' | '\u{2229}' | '\u{222A}' | '\u{2268}' | '\u{2269}' | '\u{2272}' | '\u{2273}'
+            | '\u{228A}' | '\u{228B}' | '\u{2295}' | '\u{2297}' | '\u{229C}' | '\u{22DA}' | '\u{22DB}'
+            | '\u{2A3C}' | '\u{2A3D}' | '\u{2A9D}' | '\u{2A9E}' | '\u{2AAC}' | '\u{2AAD}' | '\u{2ACB}'
+            | '\u{2ACC}'
+    )
+}
+
+/// Unusual space characters used by space-substitution schemes
+pub(crate) fn is_unusual_space(c: char) -> bool {
+    matches!(c, '\u{1680}' | '\u{2000}'..='\u{200A}' | '\u{202F}' | '\u{205F}')
+}
+
+/// Merge flagged char positions into byte ranges
+fn spans(text: &str, chars: &[(usize, char)], positions: &[usize]) -> Vec<Range<usize>> {
+    let mut spans: Vec<Range<usize>> = Vec::new();
+    for &i in positions {
+        let start = chars[i].0;
+        let end = chars.get(i + 1).map_or(text.len(), |&(offset, _)| offset);
+        match spans.last_mut() {
+            Some(last) if last.end == start => last.end = end,
+            _ => spans.push(start..end),
+        }
+    }
+    spans
+}
+
+/// Confidence that grows towards 1 with the amount of evidence
+fn confidence(evidence: f32) -> f32 {
+    1.0 - 0.5f32.powf(evidence)
+}
+
+fn finding(
+    scheme: Scheme,
+    text: &str,
+    chars: &[(usize, char)],
+    positions: &[usize],
+    estimate: usize,
+    evidence: f32,
+    waterscape_len: Option<usize>,
+) -> Option<Finding> {
+    if positions.is_empty() && waterscape_len.is_none() {
+        return None;
+    }
+    Some(Finding {
+        scheme,
+        spans: spans(text, chars, positions),
+        symbols: positions.len(),
+        estimated_bytes: waterscape_len.unwrap_or(estimate),
+        confidence: if waterscape_len.is_some() { 1.0 } else { confidence(evidence) },
+        waterscape_format: waterscape_len.is_some(),
+    })
+}
+
+fn scan_zero_width(text: &str, chars: &[(usize, char)]) -> Option<Finding> {
+    let plain: Vec<char> = chars.iter().map(|&(_, c)| c).collect();
+    let positions: Vec<usize> = (0..plain.len())
+        // Selectors are reported by the variation selector scan
+        .filter(|&i| is_invisible(plain[i]) && !variation::is_any_selector(plain[i]))
+        .filter(|&i| !is_legitimate_invisible(&plain, i))
+        .collect();
+
+    // Distinct symbols bound the bits each character can carry
+    let mut alphabet: Vec<char> = positions.iter().map(|&i| plain[i]).collect();
+    alphabet.sort_unstable();
+    alphabet.dedup();
+    let bits = (usize::BITS - alphabet.len().leading_zeros()).saturating_sub(1).max(1) as usize;
+
+    let waterscape_len = fec::extract_from_text(text)
+        .or_else(|_| ZeroWidthCodec::default().extract(text))
+        .ok()
+        .map(|data| data.len());
+    let evidence = positions.len() as f32 / 2.0;
+    finding(Scheme::ZeroWidth, text, chars, &positions, positions.len() * bits / 8, evidence, waterscape_len)
+}
+
+fn scan_tags(text: &str, chars: &[(usize, char)]) -> Option<Finding> {
+    let plain: Vec<char> = chars.iter().map(|&(_, c)| c).collect();
+    let positions = tags::payload_tag_positions(&plain);
+    let waterscape_len = tags::extract_from_text(text).ok().map(|data| data.len());
+    finding(
+        Scheme::UnicodeTags,
+        text,
+        chars,
+        &positions,
+        positions.len() * 7 / 8,
+        positions.len() as f32,
+        waterscape_len,
+    )
+}
+
+fn scan_variation_selectors(text: &str, chars: &[(usize, char)]) -> Option<Finding> {
+    let mut positions = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        if !variation::is_any_selector(chars[i].1) {
+            i += 1;
+            continue;
+        }
+        let start = i;
+        while i < chars.len() && variation::is_any_selector(chars[i].1) {
+            i += 1;
+        }
+        let base = start.checked_sub(1).map(|j| chars[j].1);
+        let run: Vec<char> = chars[start..i].iter().map(|&(_, c)| c).collect();
+        if !is_legitimate_selector_run(base, &run) {
+            positions.extend(start..i);
+        }
+    }
+
+    let waterscape_len = variation::extract_from_text(text).ok().map(|data| data.len());
+    let evidence = positions.len() as f32 / 2.0;
+    finding(Scheme::VariationSelectors, text, chars, &positions, positions.len(), evidence, waterscape_len)
+}
+
+/// Char positions of homoglyphs in words that mix them with Latin letters
+pub(crate) fn mixed_script_homoglyphs(chars: &[char]) -> (Vec<usize>, usize) {
+    let latin = chars.iter().filter(|c| c.is_ascii_alphabetic()).count();
+    let foreign = chars.iter().filter(|c| c.is_alphabetic() && !c.is_ascii()).count();
+
+    let mut positions = Vec::new();
+    let mut words = 0;
+    let mut i = 0;
+    while i < chars.len() {
+        if !chars[i].is_alphabetic() {
+            i += 1;
+            continue;
+        }
+        let start = i;
+        while i < chars.len() && chars[i].is_alphabetic() {
+            i += 1;
+        }
+        let word = &chars[start..i];
+        let glyphs: Vec<usize> = (start..i).filter(|&j| homoglyph::is_homoglyph(chars[j])).collect();
+        let has_latin = word.iter().any(char::is_ascii_alphabetic);
+        // Words made only of homoglyphs count too when the text is mostly Latin
+        let disguised = glyphs.len() == word.len() && latin > foreign;
+        if !glyphs.is_empty() && (has_latin || disguised) {
+            positions.extend(glyphs);
+            words += 1;
+        }
+    }
+    (positions, words)
+}
+
+fn scan_homoglyphs(text: &str, chars: &[(usize, char)]) -> Option<Finding> {
+    let plain: Vec<char> = chars.iter().map(|&(_, c)| c).collect();
+    let (positions, words) = mixed_script_homoglyphs(&plain);
+    let waterscape_len = homoglyph::extract_from_text(text).ok().map(|data| data.len());
+    // Every substitutable letter can carry a bit once substitution is in use
+    let estimate = if positions.is_empty() { 0 } else { homoglyph::slot_count(text) / 8 };
+    finding(Scheme::Homoglyphs, text, chars, &positions, estimate, words as f32, waterscape_len)
+}
+
+fn scan_whitespace(text: &str, chars: &[(usize, char)]) -> Option<Finding> {
+    let mut positions = Vec::new();
+    let mut anomalous_lines = 0;
+    let mut line_start = 0;
+    for i in 0..=chars.len() {
+        let at_line_end = chars.get(i).is_none_or(|&(_, c)| c == '\n');
+        if !at_line_end {
+            continue;
+        }
+        let mut end = i;
+        if end > line_start && chars[end - 1].1 == '\r' {
+            end -= 1;
+        }
+        let mut start = end;
+        while start > line_start && matches!(chars[start - 1].1, ' ' | '\t') {
+            start -= 1;
+        }
+        let run: Vec<char> = chars[start..end].iter().map(|&(_, c)| c).collect();
+        // One or two trailing spaces are normal (Markdown line breaks)
+        let mixed = run.contains(&' ') && run.contains(&'\t');
+        if start > line_start && (run.len() > 2 || mixed) {
+            positions.extend(start..end);
+            anomalous_lines += 1;
+        }
+        line_start = i + 1;
+    }
+
+    let unusual: Vec<usize> = (0..chars.len()).filter(|&i| is_unusual_space(chars[i].1)).collect();
+    let evidence = anomalous_lines as f32 + unusual.len() as f32 / 2.0;
+    let estimate = (positions.len() + unusual.len()) / 8;
+    positions.extend(unusual);
+    positions.sort_unstable();
+
+    let waterscape_len = whitespace::extract_from_text(text).ok().map(|data| data.len());
+    finding(Scheme::Whitespace, text, chars, &positions, estimate, evidence, waterscape_len)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stego;
+
+    const COVER: &str = "Our weekly sync moved to Thursday.\nPlease update your calendars accordingly.\n\
+                         Coffee and pastries will be provided as usual, see you there.";
+
+    #[test]
+    fn test_clean_text() {
+        let text = "Plain text with emoji \u{1F44D}\u{FE0F}, a family \u{1F468}\u{200D}\u{1F469}\u{200D}\u{1F467}, \
+                    a flag \u{1F3F4}\u{E0067}\u{E0062}\u{E0073}\u{E0063}\u{E0074}\u{E007F} and Привет мир.  \nDone";
+        let report = scan(text);
+        assert!(report.is_clean(), "{:?}", report);
+        assert_eq!(report.confidence(), 0.0);
+    }
+
+    #[test]
+    fn test_waterscape_carriers() {
+        let data = b"moderation test";
+        let carriers: [(Scheme, String); 5] = [
+            (Scheme::ZeroWidth, stego::hide_in_text(COVER, data).unwrap()),
+            (Scheme::UnicodeTags, tags::hide_in_text(COVER, data).unwrap()),
+            (Scheme::VariationSelectors, variation::hide_in_text(COVER, data).unwrap()),
+            (Scheme::Homoglyphs, homoglyph::hide_in_text(COVER, b"hi").unwrap()),
+            (Scheme::Whitespace, whitespace::hide_in_text(COVER, data).unwrap()),
+        ];
+
+        for (scheme, text) in carriers {
+            let report = scan(&text);
+            let finding = report.finding(scheme).unwrap_or_else(|| panic!("{:?} not detected", scheme));
+            assert!(finding.waterscape_format, "{:?}", scheme);
+            assert_eq!(finding.confidence, 1.0);
+            assert!(!finding.spans.is_empty());
+            assert_eq!(report.findings.len(), 1, "{:?}: {:?}", scheme, report);
+        }
+    }
+
+    #[test]
+    fn test_third_party_zero_width() {
+        // A foreign scheme: bits as U+200B/U+200C runs after words
+        let text = "Hello\u{200B}\u{200C}\u{200C}\u{200B}\u{200B}\u{200C}\u{200B}\u{200C} world\u{200C}\u{200B}";
+        let report = scan(text);
+        let finding = report.finding(Scheme::ZeroWidth).unwrap();
+
+        assert!(!finding.waterscape_format);
+        assert_eq!(finding.symbols, 10);
+        assert_eq!(finding.estimated_bytes, 1);
+        assert_eq!(finding.spans, vec![5..29, 35..41]);
+        assert!(finding.confidence > 0.9);
+    }
+
+    #[test]
+    fn test_selectors_on_plain_letters() {
+        // A third-party scheme: one of VS1-VS14 after each Cyrillic letter
+        let text = "При\u{FE01}в\u{FE00}ет м\u{FE03}ир";
+        let finding = scan(text).finding(Scheme::VariationSelectors).cloned().unwrap();
+
+        assert!(!finding.waterscape_format);
+        assert_eq!(finding.symbols, 3);
+        assert_eq!(&text[finding.spans[0].clone()], "\u{FE01}");
+
+        assert!(scan("A \u{2229}\u{FE00} B and 0\u{FE00}").is_clean());
+    }
+
+    #[test]
+    fn test_homoglyph_mixing() {
+        let text = "Please vеrify your pаssword here";
+        let finding = scan(text).finding(Scheme::Homoglyphs).cloned().unwrap();
+
+        assert_eq!(finding.symbols, 2);
+        assert_eq!(finding.spans.len(), 2);
+        assert_eq!(&text[finding.spans[0].clone()], "е");
+        assert_eq!(finding.confidence, 0.75);
+    }
+
+    #[test]
+    fn test_whitespace_anomalies() {
+        let text = "line one \t \nline two  \nline\u{2009}three";
+        let finding = scan(text).finding(Scheme::Whitespace).cloned().unwrap();
+
+        assert_eq!(finding.symbols, 4);
+        assert!(!finding.waterscape_format);
+    }
+}
@@ -19,8 +19,9 @@ pub enum WaterscapeError {
     #[error("Key exchange failed: {0}")]
     KeyExchange(String),
 
-    #[error("Cover text too short for payload (need {needed} chars, have {available})")]
-    CoverTextTooShort { needed: usize, available: usize },
+    /// Counts are in the carrier's capacity unit (chars, lines, ...)
+    #[error("Cover text too short for payload (need {needed} {unit}, have {available})")]
+    CoverTextTooShort { needed: usize, available: usize, unit: &'static str },
 
     #[error("No hidden message found in text")]
     NoHiddenMessage,
@@ -31,11 +32,33 @@ pub enum WaterscapeError {
     #[error("Agent not authorized to read this message")]
     Unauthorized,
 
+    #[error("Message timestamp {timestamp} outside freshness window (now {now})")]
+    StaleMessage { timestamp: u64, now: u64 },
+
+    #[error("Message has already been received (replay)")]
+    ReplayedMessage,
+
+    #[error("No key for group epoch {epoch}")]
+    UnknownEpoch { epoch: u32 },
+
+    /// The sender is not a member of the group, or may not post in it
+    #[error("Sender {sender} is not permitted to post in this group")]
+    SenderNotPermitted { sender: String },
+
+    #[error("No sender chain from {sender}; its distribution has not been received")]
+    UnknownSenderChain { sender: String },
+
+    #[error("Group membership error: {0}")]
+    Membership(String),
+
     #[error("Protocol version mismatch: expected {expected}, got {got}")]
     VersionMismatch { expected: u8, got: u8 },
 
     #[error("Serialization error: {0}")]
     Serialization(String),
+
+    #[error("I/O error: {0}")]
+    Io(String),
 }
 
 impl From<chacha20poly1305::Error> for WaterscapeError {
@@ -55,3 +78,9 @@ impl From<serde_json::Error> for WaterscapeError {
         WaterscapeError::Serialization(e.to_string())
     }
 }
+
+impl From<std::io::Error> for WaterscapeError {
+    fn from(e: std::io::Error) -> Self {
+        WaterscapeError::Io(e.to_string())
+    }
+}
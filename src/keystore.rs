@@ -0,0 +1,382 @@
+//! Passphrase-encrypted keystore for agent identities
+//!
+//! A keystore holds a full [`AgentBackup`] (name, both private keys, creation time)
+//! encrypted with ChaCha20-Poly1305. The encryption key is derived from a passphrase
+//! using Argon2id, so brute-forcing a stolen keystore file is expensive.
+//!
+//! The KDF parameters are read from the file before anything is authenticated, so
+//! they are checked against fixed maxima before Argon2 runs and then bound into
+//! the AEAD associated data together with the version and salt.
+//!
+//! ## File format
+//! ```text
+//! {
+//!   "version": 2,
+//!   "kdf": { "m_cost": 19456, "t_cost": 2, "p_cost": 1, "salt": "<hex>" },
+//!   "nonce": "<hex>",
+//!   "ciphertext": "<hex>"
+//! }
+//! ```
+
+use std::io::Write;
+use std::path::Path;
+
+use argon2::{Algorithm, Argon2, Params, Version};
+use rand::rngs::OsRng;
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroize;
+
+use crate::agent::{Agent, AgentBackup};
+use crate::crypto::{self, KEY_SIZE, NONCE_SIZE};
+use crate::error::WaterscapeError;
+use crate::Result;
+
+pub const KEYSTORE_VERSION: u8 = 2;
+pub const SALT_SIZE: usize = 16;
+
+/// Largest accepted memory cost (1 GiB, in KiB)
+pub const MAX_M_COST: u32 = 1024 * 1024;
+/// Largest accepted number of iterations
+pub const MAX_T_COST: u32 = 64;
+/// Largest accepted degree of parallelism
+pub const MAX_P_COST: u32 = 16;
+
+/// Argon2id parameters used to derive the keystore encryption key
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KdfParams {
+    /// Memory cost in KiB
+    pub m_cost: u32,
+    /// Number of iterations
+    pub t_cost: u32,
+    /// Degree of parallelism
+    pub p_cost: u32,
+    #[serde(with = "hex::serde")]
+    pub salt: [u8; SALT_SIZE],
+}
+
+impl KdfParams {
+    /// Create parameters with the given costs and a fresh random salt
+    pub fn new(m_cost: u32, t_cost: u32, p_cost: u32) -> Self {
+        let mut salt = [0u8; SALT_SIZE];
+        rand::RngCore::fill_bytes(&mut OsRng, &mut salt);
+        Self {
+            m_cost,
+            t_cost,
+            p_cost,
+            salt,
+        }
+    }
+
+    /// Derive the encryption key from a passphrase
+    fn derive_key(&self, passphrase: &str) -> Result<[u8; KEY_SIZE]> {
+        if self.m_cost > MAX_M_COST || self.t_cost > MAX_T_COST || self.p_cost > MAX_P_COST {
+            return Err(WaterscapeError::Crypto(format!(
+                "KDF parameters exceed limits (m_cost {}, t_cost {}, p_cost {})",
+                self.m_cost, self.t_cost, self.p_cost
+            )));
+        }
+        let params = Params::new(self.m_cost, self.t_cost, self.p_cost, Some(KEY_SIZE))
+            .map_err(|e| WaterscapeError::Crypto(format!("Invalid KDF parameters: {}", e)))?;
+        let argon2 = Argon2::new(Algorithm::Argon2id, Version::V0x13, params);
+
+        let mut key = [0u8; KEY_SIZE];
+        argon2
+            .hash_password_into(passphrase.as_bytes(), &self.salt, &mut key)
+            .map_err(|e| WaterscapeError::Crypto(format!("Key derivation failed: {}", e)))?;
+        Ok(key)
+    }
+
+    /// Associated data binding the keystore version and these parameters
+    fn associated_data(&self, version: u8) -> Vec<u8> {
+        let mut aad = Vec::with_capacity(1 + 12 + SALT_SIZE);
+        aad.push(version);
+        aad.extend_from_slice(&self.m_cost.to_be_bytes());
+        aad.extend_from_slice(&self.t_cost.to_be_bytes());
+        aad.extend_from_slice(&self.p_cost.to_be_bytes());
+        aad.extend_from_slice(&self.salt);
+        aad
+    }
+}
+
+impl Default for KdfParams {
+    /// OWASP-recommended Argon2id baseline (19 MiB, 2 iterations)
+    fn default() -> Self {
+        Self::new(19 * 1024, 2, 1)
+    }
+}
+
+/// An encrypted agent identity
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Keystore {
+    pub version: u8,
+    pub kdf: KdfParams,
+    #[serde(with = "hex::serde")]
+    pub nonce: [u8; NONCE_SIZE],
+    #[serde(with = "hex::serde")]
+    pub ciphertext: Vec<u8>,
+}
+
+impl Keystore {
+    /// Encrypt an agent with a passphrase using default KDF parameters
+    pub fn encrypt(agent: &Agent, passphrase: &str) -> Result<Self> {
+        Self::encrypt_with_params(agent, passphrase, KdfParams::default())
+    }
+
+    /// Encrypt an agent with a passphrase using explicit KDF parameters
+    pub fn encrypt_with_params(agent: &Agent, passphrase: &str, kdf: KdfParams) -> Result<Self> {
+        let mut plaintext = agent.export_backup().to_bytes()?;
+        let mut key = kdf.derive_key(passphrase)?;
+        let nonce = crypto::generate_nonce();
+
+        let aad = kdf.associated_data(KEYSTORE_VERSION);
+        let ciphertext = crypto::encrypt_with_aad(&key, &nonce, &plaintext, &aad);
+        key.zeroize();
+        plaintext.zeroize();
+
+        Ok(Self {
+            version: KEYSTORE_VERSION,
+            kdf,
+            nonce,
+            ciphertext: ciphertext?,
+        })
+    }
+
+    /// Decrypt the stored agent
+    ///
+    /// Returns [`WaterscapeError::AuthenticationFailed`] if the passphrase is wrong
+    /// or the keystore has been tampered with.
+    pub fn decrypt(&self, passphrase: &str) -> Result<Agent> {
+        if self.version != KEYSTORE_VERSION {
+            return Err(WaterscapeError::VersionMismatch {
+                expected: KEYSTORE_VERSION,
+                got: self.version,
+            });
+        }
+
+        let aad = self.kdf.associated_data(KEYSTORE_VERSION);
+        let mut key = self.kdf.derive_key(passphrase)?;
+        let plaintext = crypto::decrypt_with_aad(&key, &self.nonce, &self.ciphertext, &aad);
+        key.zeroize();
+
+        let mut plaintext = plaintext?;
+        let backup = AgentBackup::from_bytes(&plaintext);
+        plaintext.zeroize();
+        Agent::from_backup(&backup?)
+    }
+
+    /// Re-encrypt the stored agent under a new passphrase with a fresh salt
+    pub fn change_passphrase(&self, old_passphrase: &str, new_passphrase: &str) -> Result<Self> {
+        let agent = self.decrypt(old_passphrase)?;
+        let kdf = KdfParams::new(self.kdf.m_cost, self.kdf.t_cost, self.kdf.p_cost);
+        Self::encrypt_with_params(&agent, new_passphrase, kdf)
+    }
+
+    /// Serialize to bytes
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        serde_json::to_vec_pretty(self).map_err(|e| WaterscapeError::Serialization(e.to_string()))
+    }
+
+    /// Deserialize from bytes
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        serde_json::from_slice(bytes).map_err(|e| WaterscapeError::Serialization(e.to_string()))
+    }
+
+    /// Read a keystore from a file
+    pub fn read(path: impl AsRef<Path>) -> Result<Self> {
+        let bytes = std::fs::read(path)?;
+        Self::from_bytes(&bytes)
+    }
+
+    /// Write the keystore to a file, replacing it atomically
+    pub fn write(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        let file_name = path
+            .file_name()
+            .ok_or_else(|| WaterscapeError::Io(format!("Not a file path: {}", path.display())))?;
+        // Unique name next to the target, so no existing file is touched
+        let tmp_path = path.with_file_name(format!(
+            ".{}.{:016x}.tmp",
+            file_name.to_string_lossy(),
+            rand::random::<u64>()
+        ));
+
+        let mut options = std::fs::OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+
+        let file = options.open(&tmp_path)?;
+        let result = self
+            .write_to(file)
+            .and_then(|_| Ok(std::fs::rename(&tmp_path, path)?));
+        if result.is_err() {
+            // Only the file created above is removed
+            let _ = std::fs::remove_file(&tmp_path);
+        }
+        result
+    }
+
+    /// Write the serialized keystore to an open file and flush it to disk
+    fn write_to(&self, mut file: std::fs::File) -> Result<()> {
+        file.write_all(&self.to_bytes()?)?;
+        file.sync_all()?;
+        Ok(())
+    }
+
+    /// Encrypt an agent and save it to a file
+    pub fn save(path: impl AsRef<Path>, agent: &Agent, passphrase: &str) -> Result<()> {
+        Self::encrypt(agent, passphrase)?.write(path)
+    }
+
+    /// Load and decrypt an agent from a file
+    pub fn load(path: impl AsRef<Path>, passphrase: &str) -> Result<Agent> {
+        Self::read(path)?.decrypt(passphrase)
+    }
+
+    /// Change the passphrase of a keystore file in place
+    pub fn change_passphrase_file(
+        path: impl AsRef<Path>,
+        old_passphrase: &str,
+        new_passphrase: &str,
+    ) -> Result<()> {
+        let path = path.as_ref();
+        Self::read(path)?
+            .change_passphrase(old_passphrase, new_passphrase)?
+            .write(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Low-cost parameters to keep tests fast
+    fn test_params() -> KdfParams {
+        KdfParams::new(64, 1, 1)
+    }
+
+    #[test]
+    fn test_encrypt_decrypt() {
+        let agent = Agent::new("stored-agent");
+        let keystore = Keystore::encrypt_with_params(&agent, "correct horse", test_params()).unwrap();
+
+        let restored = keystore.decrypt("correct horse").unwrap();
+        let identity = restored.public_identity();
+
+        assert_eq!(identity.name, "stored-agent");
+        assert_eq!(identity.signing_key, agent.public_identity().signing_key);
+        assert_eq!(identity.exchange_key, agent.public_identity().exchange_key);
+        assert_eq!(restored.created_at(), agent.created_at());
+    }
+
+    #[test]
+    fn test_wrong_passphrase() {
+        let agent = Agent::new("stored-agent");
+        let keystore = Keystore::encrypt_with_params(&agent, "correct horse", test_params()).unwrap();
+
+        assert!(matches!(
+            keystore.decrypt("battery staple"),
+            Err(WaterscapeError::AuthenticationFailed)
+        ));
+    }
+
+    #[test]
+    fn test_change_passphrase() {
+        let agent = Agent::new("stored-agent");
+        let keystore = Keystore::encrypt_with_params(&agent, "old", test_params()).unwrap();
+
+        let rotated = keystore.change_passphrase("old", "new").unwrap();
+        assert_ne!(rotated.kdf.salt, keystore.kdf.salt);
+        assert!(rotated.decrypt("old").is_err());
+        assert_eq!(
+            rotated.decrypt("new").unwrap().public_identity().signing_key,
+            agent.public_identity().signing_key
+        );
+    }
+
+    #[test]
+    fn test_file_roundtrip() {
+        let agent = Agent::new("file-agent");
+        let path = std::env::temp_dir().join(format!("waterscape-keystore-{}.json", rand::random::<u64>()));
+
+        Keystore::encrypt_with_params(&agent, "pass", test_params())
+            .unwrap()
+            .write(&path)
+            .unwrap();
+        Keystore::change_passphrase_file(&path, "pass", "new-pass").unwrap();
+        let restored = Keystore::load(&path, "new-pass").unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(
+            restored.public_identity().exchange_key,
+            agent.public_identity().exchange_key
+        );
+    }
+
+    #[test]
+    fn test_write_leaves_neighbours_alone() {
+        let agent = Agent::new("file-agent");
+        let dir = std::env::temp_dir().join(format!("waterscape-keystore-{}", rand::random::<u64>()));
+        std::fs::create_dir(&dir).unwrap();
+        let json_path = dir.join("agent.json");
+        let tmp_path = dir.join("agent.tmp");
+
+        // An unrelated file with the old temporary name survives
+        std::fs::write(&tmp_path, b"notes").unwrap();
+        let keystore = Keystore::encrypt_with_params(&agent, "pass", test_params()).unwrap();
+        keystore.write(&json_path).unwrap();
+        assert_eq!(std::fs::read(&tmp_path).unwrap(), b"notes");
+
+        // A keystore named like a temporary file is replaced, not deleted
+        keystore.write(&tmp_path).unwrap();
+        let restored = Keystore::load(&tmp_path, "pass").unwrap();
+        let mut names: Vec<_> = std::fs::read_dir(&dir).unwrap().map(|e| e.unwrap().file_name()).collect();
+        names.sort();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(restored.public_identity().signing_key, agent.public_identity().signing_key);
+        assert_eq!(names, ["agent.json", "agent.tmp"]);
+    }
+
+    #[test]
+    fn test_oversized_kdf_params_rejected() {
+        let agent = Agent::new("stored-agent");
+        let mut keystore = Keystore::encrypt_with_params(&agent, "pass", test_params()).unwrap();
+
+        // Rejected before Argon2 would try to allocate 4 TiB
+        keystore.kdf.m_cost = u32::MAX;
+        assert!(matches!(
+            keystore.decrypt("pass"),
+            Err(WaterscapeError::Crypto(_))
+        ));
+    }
+
+    #[test]
+    fn test_kdf_params_authenticated() {
+        let agent = Agent::new("stored-agent");
+        let keystore = Keystore::encrypt_with_params(&agent, "pass", test_params()).unwrap();
+
+        let mut tampered = keystore.clone();
+        tampered.kdf.t_cost += 1;
+        assert!(matches!(
+            tampered.decrypt("pass"),
+            Err(WaterscapeError::AuthenticationFailed)
+        ));
+
+        let mut tampered = keystore.clone();
+        tampered.version = 1;
+        assert!(matches!(
+            tampered.decrypt("pass"),
+            Err(WaterscapeError::VersionMismatch { got: 1, .. })
+        ));
+    }
+}
@@ -32,6 +32,13 @@ pub mod protocol;
 pub mod agent;
 pub mod error;
 pub mod skill;
+pub mod keystore;
+pub mod replay;
+pub mod detector;
+pub mod sanitizer;
+pub mod sender_keys;
+pub mod ratchet_tree;
+pub mod simulator;
 
 #[cfg(feature = "moltbook")]
 pub mod moltbook;
@@ -40,8 +47,18 @@ pub mod moltbook;
 pub mod wasm;
 
 pub use agent::Agent;
-pub use protocol::{WaterscapeChannel, Waterscape, WaterscapeGroup};
+pub use protocol::{CoverIntegrity, DecodeOutcome, DecodedMessage, GroupKeyEnvelope, GroupMember, GroupRole, WaterscapeChannel, Waterscape, WaterscapeGroup};
 pub use error::WaterscapeError;
+pub use keystore::Keystore;
+pub use replay::{ReplayGuard, ReplayPolicy};
+pub use ratchet_tree::{Commit, RatchetTree, TreeGroup, Welcome};
+pub use sender_keys::{SenderKeyDistribution, SenderKeyGroup};
+pub use stego::{StegoCodec, ZeroWidthCodec};
+pub use stego::fec::FecCodec;
+pub use stego::homoglyph::HomoglyphCodec;
+pub use stego::tags::TagCodec;
+pub use stego::variation::VariationSelectorCodec;
+pub use stego::whitespace::WhitespaceCodec;
 pub use skill::{WaterscapeSkill, SkillAction, SkillResponse};
 
 #[cfg(feature = "moltbook")]
@@ -10,7 +10,11 @@ use reqwest::Client;
 use serde::{Deserialize, Serialize};
 
 use crate::agent::{Agent, PublicIdentity};
+#[cfg(feature = "moltbook")]
+use crate::agent::AgentRegistry;
 use crate::error::WaterscapeError;
+#[cfg(feature = "moltbook")]
+use crate::protocol::DecodeOutcome;
 use crate::protocol::Waterscape;
 use crate::Result;
 
@@ -318,6 +322,31 @@ impl<C: MoltbookClient> WaterscapeMoltbook<C> {
         Ok(results)
     }
 
+    /// Scan posts for hidden messages, resolving senders from a local registry
+    ///
+    /// Unlike [`scan_posts`](Self::scan_posts), this does not trust the post author
+    /// field: the sender is identified by the signing key carried in the message.
+    pub async fn scan_posts_with_registry(
+        &self,
+        submolt: &str,
+        limit: usize,
+        registry: &AgentRegistry,
+    ) -> Result<Vec<(MoltbookPost, Option<DecodeOutcome>)>> {
+        let posts = self.client.get_posts(submolt, limit).await?;
+
+        Ok(posts
+            .into_iter()
+            .map(|post| {
+                let decoded = if Waterscape::has_hidden_message(&post.content) {
+                    Waterscape::decode_from_registry(&self.agent, registry, &post.content).ok()
+                } else {
+                    None
+                };
+                (post, decoded)
+            })
+            .collect())
+    }
+
     /// Scan a specific post and its comments for hidden messages
     pub async fn scan_post(&self, post_id: &str) -> Result<(MoltbookPost, Vec<(MoltbookComment, Option<String>)>)> {
         let post = self.client.get_post(post_id).await?;
@@ -469,4 +498,36 @@ mod tests {
         assert_eq!(post.comments.len(), 1);
         assert_eq!(post.comments[0].id, comment_id);
     }
+
+    #[tokio::test]
+    async fn test_scan_posts_with_registry() {
+        let alice = Agent::new("alice");
+        let bob = Agent::new("bob");
+        let alice_identity = alice.public_identity();
+
+        let alice_moltbook = WaterscapeMoltbook::new(alice, MockMoltbookClient::new());
+        alice_moltbook
+            .send_post("m/test", "Lovely day!", "hidden", &bob.public_identity())
+            .await
+            .unwrap();
+        let posts = alice_moltbook.client.get_posts("m/test", 10).await.unwrap();
+
+        let bob_client = MockMoltbookClient::new();
+        for post in posts {
+            bob_client.add_post(post);
+        }
+        let bob_moltbook = WaterscapeMoltbook::new(bob, bob_client);
+
+        let mut registry = AgentRegistry::new();
+        registry.register(alice_identity);
+
+        let results = bob_moltbook
+            .scan_posts_with_registry("m/test", 10, &registry)
+            .await
+            .unwrap();
+        assert!(matches!(
+            &results[0].1,
+            Some(DecodeOutcome::Known { content, .. }) if content == "hidden"
+        ));
+    }
 }
@@ -4,45 +4,252 @@
 //!
 //! ## Message Format
 //! ```text
-//! +--------+--------+--------+----------+------------+
-//! | Version| Nonce  | Sender | Encrypted|  Signature |
-//! | 1 byte | 12 byte| 32 byte| Payload  |  64 bytes  |
-//! +--------+--------+--------+----------+------------+
+//! +--------+--------+--------+-----------+----------+------------+
+//! | Version| Nonce  | Sender | Ephemeral | Encrypted|  Signature |
+//! | 1 byte | 12 byte| 32 byte|  32 byte  | Payload  |  64 bytes  |
+//! +--------+--------+--------+-----------+----------+------------+
 //! ```
+//!
+//! The encrypted payload takes all bytes between the fixed header and the
+//! trailing signature. Messages from older releases were JSON objects with
+//! hex-encoded fields; they start with `{` and are still accepted.
+//!
+//! Version 2 signs a transcript of every header field, the recipient's key and
+//! the ciphertext (see [`WaterscapeMessage::transcript`]). Version 1 signed the
+//! ciphertext only and is rejected unless a channel explicitly allows it, or the
+//! message is read with [`Waterscape::decode_legacy`].
+//!
+//! ## Payload Format
+//! ```text
+//! +--------+-------+-----------+--------------------------+-------------------+---------+
+//! | Format | Flags | Timestamp | Metadata (if flag bit 0) | Cover hash (if    | Content |
+//! | 1 byte | 1 byte| 8 byte BE | 2 byte BE length + UTF-8 | flag bit 1) 32 B  |  UTF-8  |
+//! +--------+-------+-----------+--------------------------+-------------------+---------+
+//! ```
+//!
+//! The cover hash binds the hidden message to the visible text it was sent in;
+//! see [`cover_digest`].
+
+use std::collections::BTreeMap;
 
 use ed25519_dalek::Signature;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use unicode_normalization::UnicodeNormalization;
 use x25519_dalek::PublicKey as X25519PublicKey;
+use zeroize::Zeroize;
 
 /// Returns current unix timestamp in seconds, compatible with wasm32.
 #[cfg(target_arch = "wasm32")]
-fn unix_timestamp_secs() -> u64 {
+pub(crate) fn unix_timestamp_secs() -> u64 {
     (js_sys::Date::now() / 1000.0) as u64
 }
 
 #[cfg(not(target_arch = "wasm32"))]
-fn unix_timestamp_secs() -> u64 {
+pub(crate) fn unix_timestamp_secs() -> u64 {
     std::time::SystemTime::now()
         .duration_since(std::time::UNIX_EPOCH)
         .unwrap_or_default()
         .as_secs()
 }
 
-use crate::agent::{Agent, PublicIdentity};
-use crate::crypto::{self, KEY_SIZE, NONCE_SIZE};
+use crate::agent::{Agent, AgentRegistry, PublicIdentity};
+use crate::crypto::{self, EphemeralKeyPair, SharedSecret, KEY_SIZE, NONCE_SIZE};
 use crate::error::WaterscapeError;
-use crate::stego;
+use crate::replay::{MessageId, ReplayGuard};
+use crate::sanitizer;
+use crate::stego::{self, CoverRequirement, StegoCodec, ZeroWidthCodec};
 use crate::Result;
 
-pub const PROTOCOL_VERSION: u8 = 1;
+pub const PROTOCOL_VERSION: u8 = 2;
+/// Version whose signature covers the ciphertext only
+pub const LEGACY_VERSION: u8 = 1;
+const CONTEXT_SIGN: &[u8] = b"waterscape-v2-sign";
+const CONTEXT_GROUP_ID: &[u8] = b"waterscape-v2-group-id";
+const CONTEXT_ENVELOPE: &[u8] = b"waterscape-v2-envelope";
+const CONTEXT_ENVELOPE_SIGN: &[u8] = b"waterscape-v2-envelope-sign";
+/// Version of the group key envelope format
+pub const ENVELOPE_VERSION: u8 = 1;
 const CONTEXT_ENCRYPT: &[u8] = b"waterscape-v1-encrypt";
+pub(crate) const CONTEXT_PLACEMENT: &[u8] = b"waterscape-v1-placement";
+const CONTEXT_COVER: &[u8] = b"waterscape-v1-cover";
+
+/// Size of the fixed binary header: version, nonce, sender key, ephemeral key
+const HEADER_SIZE: usize = 1 + NONCE_SIZE + 32 + 32;
+/// First byte of a legacy JSON-encoded message or payload
+const JSON_START: u8 = b'{';
+
+const PAYLOAD_FORMAT: u8 = 1;
+const PAYLOAD_FLAG_METADATA: u8 = 0b0000_0001;
+const PAYLOAD_FLAG_COVER_HASH: u8 = 0b0000_0010;
+/// Size of the visible cover text hash carried by bound messages
+pub const COVER_HASH_SIZE: usize = 32;
+/// Format, flags and timestamp
+const PAYLOAD_HEADER_SIZE: usize = 1 + 1 + 8;
 
 /// Encrypted message payload
 #[derive(Serialize, Deserialize)]
-struct EncryptedPayload {
-    content: String,
-    timestamp: u64,
-    metadata: Option<String>,
+pub(crate) struct EncryptedPayload {
+    pub(crate) content: String,
+    pub(crate) timestamp: u64,
+    pub(crate) metadata: Option<String>,
+    #[serde(default)]
+    pub(crate) cover_hash: Option<[u8; COVER_HASH_SIZE]>,
+}
+
+impl EncryptedPayload {
+    /// Serialize to the binary payload format
+    pub(crate) fn to_bytes(&self) -> Result<Vec<u8>> {
+        let mut bytes = Vec::with_capacity(
+            Self::encoded_len(self.content.len(), self.metadata.as_deref()) + self.cover_hash.map_or(0, |h| h.len()),
+        );
+        let mut flags = 0u8;
+        if self.metadata.is_some() {
+            flags |= PAYLOAD_FLAG_METADATA;
+        }
+        if self.cover_hash.is_some() {
+            flags |= PAYLOAD_FLAG_COVER_HASH;
+        }
+
+        bytes.push(PAYLOAD_FORMAT);
+        bytes.push(flags);
+        bytes.extend_from_slice(&self.timestamp.to_be_bytes());
+        if let Some(metadata) = &self.metadata {
+            let len = u16::try_from(metadata.len())
+                .map_err(|_| WaterscapeError::Serialization("Metadata too long".into()))?;
+            bytes.extend_from_slice(&len.to_be_bytes());
+            bytes.extend_from_slice(metadata.as_bytes());
+        }
+        if let Some(cover_hash) = &self.cover_hash {
+            bytes.extend_from_slice(cover_hash);
+        }
+        bytes.extend_from_slice(self.content.as_bytes());
+        Ok(bytes)
+    }
+
+    /// Size of the binary payload for the given content length and metadata,
+    /// without a cover hash
+    fn encoded_len(content_len: usize, metadata: Option<&str>) -> usize {
+        PAYLOAD_HEADER_SIZE + metadata.map_or(0, |m| 2 + m.len()) + content_len
+    }
+
+    /// Deserialize from the binary payload format, or legacy JSON
+    pub(crate) fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        if bytes.first() == Some(&JSON_START) {
+            return Ok(serde_json::from_slice(bytes)?);
+        }
+
+        let truncated = || WaterscapeError::Serialization("Truncated payload".into());
+        if bytes.len() < PAYLOAD_HEADER_SIZE {
+            return Err(truncated());
+        }
+        if bytes[0] != PAYLOAD_FORMAT {
+            return Err(WaterscapeError::Serialization(format!(
+                "Unknown payload format: {}",
+                bytes[0]
+            )));
+        }
+
+        let flags = bytes[1];
+        let timestamp = u64::from_be_bytes(bytes[2..10].try_into().map_err(|_| truncated())?);
+        let mut rest = &bytes[PAYLOAD_HEADER_SIZE..];
+
+        let metadata = if flags & PAYLOAD_FLAG_METADATA != 0 {
+            if rest.len() < 2 {
+                return Err(truncated());
+            }
+            let len = u16::from_be_bytes([rest[0], rest[1]]) as usize;
+            let metadata = rest.get(2..2 + len).ok_or_else(truncated)?;
+            rest = &rest[2 + len..];
+            Some(utf8(metadata)?)
+        } else {
+            None
+        };
+
+        let cover_hash = if flags & PAYLOAD_FLAG_COVER_HASH != 0 {
+            let hash = rest.get(..COVER_HASH_SIZE).ok_or_else(truncated)?;
+            rest = &rest[COVER_HASH_SIZE..];
+            Some(hash.try_into().map_err(|_| truncated())?)
+        } else {
+            None
+        };
+
+        Ok(Self {
+            content: utf8(rest)?,
+            timestamp,
+            metadata,
+            cover_hash,
+        })
+    }
+}
+
+fn utf8(bytes: &[u8]) -> Result<String> {
+    String::from_utf8(bytes.to_vec()).map_err(|e| WaterscapeError::Serialization(e.to_string()))
+}
+
+/// Hash of the normalized visible text a message is bound to
+///
+/// The text is sanitized of every hidden channel, NFKC-normalized and its
+/// whitespace collapsed, so carriers and platform reformatting do not change the
+/// hash while any edit to the words does.
+pub fn cover_digest(visible_text: &str) -> [u8; COVER_HASH_SIZE] {
+    let normalized: String = sanitizer::sanitize(visible_text).nfkc().collect();
+    let mut hasher = Sha256::new();
+    hasher.update(CONTEXT_COVER);
+    for (i, word) in normalized.split_whitespace().enumerate() {
+        if i > 0 {
+            hasher.update(b" ");
+        }
+        hasher.update(word.as_bytes());
+    }
+    hasher.finalize().into()
+}
+
+/// Whether the visible text matches the text a message was sent in
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CoverIntegrity {
+    /// The sender did not bind the message to its cover text
+    Unbound,
+    /// The visible text is the one the message was sent in
+    Intact,
+    /// The visible text was altered, or the payload was moved to another text
+    Mismatch,
+}
+
+impl CoverIntegrity {
+    fn check(payload: &EncryptedPayload, visible_text: &str) -> Self {
+        match &payload.cover_hash {
+            None => Self::Unbound,
+            Some(hash) if *hash == cover_digest(visible_text) => Self::Intact,
+            Some(_) => Self::Mismatch,
+        }
+    }
+}
+
+/// A decrypted message together with its sender and the state of its cover text
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct DecodedMessage {
+    /// Verified sender of the message
+    pub sender: PublicIdentity,
+    pub content: String,
+    pub timestamp: u64,
+    pub cover: CoverIntegrity,
+    /// Identifier to pass to a [`ReplayGuard`]
+    #[serde(skip)]
+    pub id: MessageId,
+}
+
+impl DecodedMessage {
+    pub(crate) fn new(message: &WaterscapeMessage, sender: PublicIdentity, payload: EncryptedPayload, visible_text: &str) -> Self {
+        Self {
+            id: message.id(),
+            sender,
+            cover: CoverIntegrity::check(&payload, visible_text),
+            content: payload.content,
+            timestamp: payload.timestamp,
+        }
+    }
 }
 
 /// Wire format for a Waterscape message
@@ -62,55 +269,185 @@ pub struct WaterscapeMessage {
 }
 
 impl WaterscapeMessage {
-    /// Serialize to bytes
+    /// Serialize to the compact binary wire format
     pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        if self.signature.len() != crypto::SIGNATURE_SIZE {
+            return Err(WaterscapeError::Serialization("Invalid signature length".into()));
+        }
+
+        let mut bytes = Vec::with_capacity(HEADER_SIZE + self.ciphertext.len() + crypto::SIGNATURE_SIZE);
+        bytes.push(self.version);
+        bytes.extend_from_slice(&self.nonce);
+        bytes.extend_from_slice(&self.sender_key);
+        bytes.extend_from_slice(&self.ephemeral_key);
+        bytes.extend_from_slice(&self.ciphertext);
+        bytes.extend_from_slice(&self.signature);
+        Ok(bytes)
+    }
+
+    /// Serialize to the legacy JSON format
+    pub fn to_json_bytes(&self) -> Result<Vec<u8>> {
         serde_json::to_vec(self).map_err(|e| WaterscapeError::Serialization(e.to_string()))
     }
 
-    /// Deserialize from bytes
+    /// Deserialize from the binary wire format, or legacy JSON
     pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
-        serde_json::from_slice(bytes).map_err(|e| WaterscapeError::Serialization(e.to_string()))
+        match bytes.first() {
+            Some(&JSON_START) => {
+                serde_json::from_slice(bytes).map_err(|e| WaterscapeError::Serialization(e.to_string()))
+            }
+            Some(&PROTOCOL_VERSION) | Some(&LEGACY_VERSION) => Self::from_binary(bytes),
+            Some(&version) => Err(WaterscapeError::VersionMismatch {
+                expected: PROTOCOL_VERSION,
+                got: version,
+            }),
+            None => Err(WaterscapeError::Serialization("Empty message".into())),
+        }
+    }
+
+    fn from_binary(bytes: &[u8]) -> Result<Self> {
+        if bytes.len() < HEADER_SIZE + crypto::SIGNATURE_SIZE {
+            return Err(WaterscapeError::Serialization("Truncated message".into()));
+        }
+
+        let (header, rest) = bytes.split_at(HEADER_SIZE);
+        let (ciphertext, signature) = rest.split_at(rest.len() - crypto::SIGNATURE_SIZE);
+
+        let mut nonce = [0u8; NONCE_SIZE];
+        let mut sender_key = [0u8; 32];
+        let mut ephemeral_key = [0u8; 32];
+        nonce.copy_from_slice(&header[1..1 + NONCE_SIZE]);
+        sender_key.copy_from_slice(&header[1 + NONCE_SIZE..1 + NONCE_SIZE + 32]);
+        ephemeral_key.copy_from_slice(&header[1 + NONCE_SIZE + 32..]);
+
+        Ok(Self {
+            version: header[0],
+            nonce,
+            sender_key,
+            ephemeral_key,
+            ciphertext: ciphertext.to_vec(),
+            signature: signature.to_vec(),
+        })
+    }
+
+    /// Canonical bytes signed by the sender of a version 2 message
+    ///
+    /// Binds the version, nonce, sender key, ephemeral key and the key of the
+    /// intended recipient to the ciphertext, so a message cannot be re-targeted
+    /// or reframed without invalidating the signature.
+    pub fn transcript(&self, recipient_key: &[u8; 32]) -> Vec<u8> {
+        let mut transcript = Vec::with_capacity(CONTEXT_SIGN.len() + HEADER_SIZE + 32 + 4 + self.ciphertext.len());
+        transcript.extend_from_slice(CONTEXT_SIGN);
+        transcript.push(self.version);
+        transcript.extend_from_slice(&self.nonce);
+        transcript.extend_from_slice(&self.sender_key);
+        transcript.extend_from_slice(&self.ephemeral_key);
+        transcript.extend_from_slice(recipient_key);
+        transcript.extend_from_slice(&(self.ciphertext.len() as u32).to_be_bytes());
+        transcript.extend_from_slice(&self.ciphertext);
+        transcript
+    }
+
+    /// Reject versions other than the current one, and version 1 unless allowed
+    pub fn check_version(&self, allow_legacy: bool) -> Result<()> {
+        match self.version {
+            PROTOCOL_VERSION => Ok(()),
+            LEGACY_VERSION if allow_legacy => Ok(()),
+            version => Err(WaterscapeError::VersionMismatch {
+                expected: PROTOCOL_VERSION,
+                got: version,
+            }),
+        }
+    }
+
+    /// Verify the sender's signature for the given recipient key
+    ///
+    /// Version 1 signatures cover the ciphertext only and ignore the recipient.
+    pub fn verify_signature(&self, recipient_key: &[u8; 32]) -> Result<()> {
+        let sig_bytes: [u8; 64] = self.signature.clone().try_into()
+            .map_err(|_| WaterscapeError::Crypto("Invalid signature length".into()))?;
+        let signature = Signature::from_bytes(&sig_bytes);
+        if self.version == LEGACY_VERSION {
+            return crypto::verify_signature(&self.sender_key, &self.ciphertext, &signature);
+        }
+        crypto::verify_signature(&self.sender_key, &self.transcript(recipient_key), &signature)
+    }
+
+    /// Identifier used for replay detection
+    pub fn id(&self) -> MessageId {
+        MessageId {
+            sender_key: self.sender_key,
+            nonce: self.nonce,
+        }
     }
 }
 
 /// A communication channel between two agents
+///
+/// The channel only holds the static-static shared secret. Every message
+/// generates a fresh ephemeral X25519 key, and the message key is derived from
+/// both the ephemeral-static and static-static exchanges. The sender discards
+/// the ephemeral secret, so compromising the sender's long-term keys does not
+/// reveal past messages; the receiver's exchange key still opens every message
+/// addressed to it (see SPECIFICATION §9.2).
 pub struct WaterscapeChannel {
     local_agent: PublicIdentity,
     remote_agent: PublicIdentity,
-    shared_key: [u8; KEY_SIZE],
+    static_secret: SharedSecret,
+    codec: Box<dyn StegoCodec>,
+    bind_cover: bool,
+    allow_legacy: bool,
 }
 
 impl WaterscapeChannel {
-    /// Establish a channel from sender to receiver
-    pub fn establish(sender: &Agent, receiver: &PublicIdentity) -> Result<(Self, [u8; 32])> {
-        let receiver_exchange_key = X25519PublicKey::from(receiver.exchange_key);
-        let shared_secret = sender.exchange_keypair().diffie_hellman(&receiver_exchange_key);
-        let shared_key = shared_secret.derive_key(CONTEXT_ENCRYPT);
-
-        let channel = Self {
-            local_agent: sender.public_identity(),
-            remote_agent: receiver.clone(),
-            shared_key,
-        };
+    /// Establish a channel between a local agent and a remote identity
+    ///
+    /// The same call is used on both sides: the sender establishes a channel
+    /// to the receiver, and the receiver establishes one to the sender.
+    pub fn establish(local: &Agent, remote: &PublicIdentity) -> Result<Self> {
+        let remote_exchange_key = X25519PublicKey::from(remote.exchange_key);
+        let static_secret = local.exchange_keypair().diffie_hellman(&remote_exchange_key);
 
-        Ok((channel, sender.exchange_keypair().public_key_bytes()))
+        Ok(Self {
+            local_agent: local.public_identity(),
+            remote_agent: remote.clone(),
+            static_secret,
+            codec: Box::new(ZeroWidthCodec::default()),
+            bind_cover: false,
+            allow_legacy: false,
+        })
     }
 
-    /// Establish channel on receiver side using sender's ephemeral key
-    pub fn establish_receiver(
-        receiver: &Agent,
-        sender: &PublicIdentity,
-        sender_ephemeral_key: &[u8; 32],
-    ) -> Result<Self> {
-        let sender_exchange_key = X25519PublicKey::from(*sender_ephemeral_key);
-        let shared_secret = receiver.exchange_keypair().diffie_hellman(&sender_exchange_key);
-        let shared_key = shared_secret.derive_key(CONTEXT_ENCRYPT);
+    /// Use a different steganographic carrier for this channel
+    pub fn with_codec(mut self, codec: impl StegoCodec + 'static) -> Self {
+        self.codec = Box::new(codec);
+        self
+    }
 
-        Ok(Self {
-            local_agent: receiver.public_identity(),
-            remote_agent: sender.clone(),
-            shared_key,
-        })
+    /// Carrier used by this channel
+    pub fn codec(&self) -> &dyn StegoCodec {
+        self.codec.as_ref()
+    }
+
+    /// Bind every message to the visible text it is sent in
+    ///
+    /// A hash of the cover text is encrypted and signed with the message, and
+    /// [`decode_verified`](Self::decode_verified) reports whether it still matches.
+    pub fn with_cover_binding(mut self, bind: bool) -> Self {
+        self.bind_cover = bind;
+        self
+    }
+
+    /// Accept version 1 messages, whose signature does not cover the header
+    pub fn with_legacy_messages(mut self, allow: bool) -> Self {
+        self.allow_legacy = allow;
+        self
+    }
+
+    /// Plan the cover text needed for a secret of `secret_len` bytes
+    pub fn plan(&self, secret_len: usize) -> CoverPlan {
+        let message_len = message_len(secret_len, Recipient::Direct) + cover_hash_len(self.bind_cover);
+        plan_message(self.codec(), message_len)
     }
 
     /// Encrypt and encode a secret message into cover text
@@ -120,65 +457,204 @@ impl WaterscapeChannel {
         cover_text: &str,
         secret_message: &str,
     ) -> Result<String> {
-        let message = self.create_message(sender, secret_message)?;
+        let cover_hash = self.bind_cover.then(|| cover_digest(cover_text));
+        let (message, mut placement_key) = self.create_message(sender, secret_message, cover_hash)?;
         let message_bytes = message.to_bytes()?;
-        stego::hide_in_text(cover_text, &message_bytes)
+        let encoded = self.codec.hide_keyed(cover_text, &message_bytes, &placement_key);
+        placement_key.zeroize();
+        encoded
     }
 
     /// Decode and decrypt a message from text
-    pub fn decode(&self, text: &str) -> Result<String> {
-        let message_bytes = stego::extract_from_text(text)?;
+    pub fn decode(&self, receiver: &Agent, text: &str) -> Result<String> {
+        let message_bytes = self.codec.extract(text)?;
+        let message = WaterscapeMessage::from_bytes(&message_bytes)?;
+        Ok(self.decrypt_message(receiver, &message)?.content)
+    }
+
+    /// Decode a message and check it against the visible text it arrived in
+    pub fn decode_verified(&self, receiver: &Agent, text: &str) -> Result<DecodedMessage> {
+        let message_bytes = self.codec.extract(text)?;
+        let message = WaterscapeMessage::from_bytes(&message_bytes)?;
+        let payload = self.decrypt_message(receiver, &message)?;
+        Ok(DecodedMessage::new(&message, self.remote_agent.clone(), payload, &self.codec.strip(text)))
+    }
+
+    /// Decode a message, rejecting stale or already seen messages
+    pub fn decode_with_guard(
+        &self,
+        receiver: &Agent,
+        text: &str,
+        guard: &mut ReplayGuard,
+    ) -> Result<String> {
+        let message_bytes = self.codec.extract(text)?;
         let message = WaterscapeMessage::from_bytes(&message_bytes)?;
-        self.decrypt_message(&message)
+        let payload = self.decrypt_message(receiver, &message)?;
+        guard.check(message.id(), payload.timestamp)?;
+        Ok(payload.content)
     }
 
-    /// Create an encrypted message
-    fn create_message(&self, sender: &Agent, content: &str) -> Result<WaterscapeMessage> {
+    /// Ensure the agent using the channel is the one it was established for
+    fn check_local_agent(&self, agent: &Agent) -> Result<()> {
+        if agent.public_identity().signing_key != self.local_agent.signing_key {
+            return Err(WaterscapeError::KeyExchange(
+                "Agent does not match the channel's local identity".into(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Derive the per-message key from the ephemeral and static shared secrets
+    fn message_key(
+        &self,
+        ephemeral_secret: &SharedSecret,
+        ephemeral_key: &[u8; 32],
+        recipient_exchange_key: &[u8; 32],
+    ) -> [u8; KEY_SIZE] {
+        let mut context = Vec::with_capacity(CONTEXT_ENCRYPT.len() + 64);
+        context.extend_from_slice(CONTEXT_ENCRYPT);
+        context.extend_from_slice(ephemeral_key);
+        context.extend_from_slice(recipient_exchange_key);
+        ephemeral_secret.derive_key_with(&self.static_secret, &context)
+    }
+
+    /// Create an encrypted message, also returning the key that drives its placement
+    fn create_message(
+        &self,
+        sender: &Agent,
+        content: &str,
+        cover_hash: Option<[u8; COVER_HASH_SIZE]>,
+    ) -> Result<(WaterscapeMessage, [u8; KEY_SIZE])> {
+        self.check_local_agent(sender)?;
+
         let nonce = crypto::generate_nonce();
 
         let payload = EncryptedPayload {
             content: content.to_string(),
             timestamp: unix_timestamp_secs(),
             metadata: None,
+            cover_hash,
         };
 
-        let payload_bytes = serde_json::to_vec(&payload)?;
-        let ciphertext = crypto::encrypt(&self.shared_key, &nonce, &payload_bytes)?;
+        // Fresh ephemeral key for this message only; the secret is consumed here
+        let ephemeral = EphemeralKeyPair::generate();
+        let ephemeral_key = ephemeral.public_key_bytes();
+        let ephemeral_secret =
+            ephemeral.diffie_hellman(&X25519PublicKey::from(self.remote_agent.exchange_key));
+        let mut key = self.message_key(&ephemeral_secret, &ephemeral_key, &self.remote_agent.exchange_key);
 
-        // Sign the ciphertext
-        let signature = sender.signing_keypair().sign(&ciphertext);
+        let payload_bytes = payload.to_bytes()?;
+        let ciphertext = crypto::encrypt(&key, &nonce, &payload_bytes);
+        let placement_key = crypto::derive_from_seed(&key, CONTEXT_PLACEMENT);
+        key.zeroize();
+        let ciphertext = ciphertext?;
 
-        Ok(WaterscapeMessage {
+        let mut message = WaterscapeMessage {
             version: PROTOCOL_VERSION,
             nonce,
             sender_key: sender.public_identity().signing_key,
-            ephemeral_key: sender.exchange_keypair().public_key_bytes(),
+            ephemeral_key,
             ciphertext,
-            signature: signature.to_bytes().to_vec(),
-        })
+            signature: Vec::new(),
+        };
+
+        // Sign the header and ciphertext for this recipient
+        let signature = sender.signing_keypair().sign(&message.transcript(&self.remote_agent.exchange_key));
+        message.signature = signature.to_bytes().to_vec();
+        Ok((message, placement_key))
     }
 
     /// Decrypt a message
-    fn decrypt_message(&self, message: &WaterscapeMessage) -> Result<String> {
+    fn decrypt_message(&self, receiver: &Agent, message: &WaterscapeMessage) -> Result<EncryptedPayload> {
+        self.check_local_agent(receiver)?;
+
         // Verify version
-        if message.version != PROTOCOL_VERSION {
-            return Err(WaterscapeError::VersionMismatch {
-                expected: PROTOCOL_VERSION,
-                got: message.version,
-            });
+        message.check_version(self.allow_legacy)?;
+
+        // The message must come from the agent on the other end of this channel
+        if message.sender_key != self.remote_agent.signing_key {
+            return Err(WaterscapeError::AuthenticationFailed);
         }
 
-        // Verify signature
-        let sig_bytes: [u8; 64] = message.signature.clone().try_into()
-            .map_err(|_| WaterscapeError::Crypto("Invalid signature length".into()))?;
-        let signature = Signature::from_bytes(&sig_bytes);
-        crypto::verify_signature(&message.sender_key, &message.ciphertext, &signature)?;
+        // Verify signature; the message must be addressed to this agent
+        message.verify_signature(&self.local_agent.exchange_key)?;
 
         // Decrypt
-        let payload_bytes = crypto::decrypt(&self.shared_key, &message.nonce, &message.ciphertext)?;
-        let payload: EncryptedPayload = serde_json::from_slice(&payload_bytes)?;
+        let ephemeral_secret = receiver
+            .exchange_keypair()
+            .diffie_hellman(&X25519PublicKey::from(message.ephemeral_key));
+        let mut key = if message.version == LEGACY_VERSION {
+            // Version 1 sent the sender's static key as `ephemeral_key`
+            ephemeral_secret.derive_key(CONTEXT_ENCRYPT)
+        } else {
+            self.message_key(&ephemeral_secret, &message.ephemeral_key, &self.local_agent.exchange_key)
+        };
+        let payload_bytes = crypto::decrypt(&key, &message.nonce, &message.ciphertext);
+        key.zeroize();
+        let payload = EncryptedPayload::from_bytes(&payload_bytes?)?;
 
-        Ok(payload.content)
+        Ok(payload)
+    }
+}
+
+/// Result of decoding a message whose sender is resolved from a registry
+#[derive(Clone, Debug)]
+pub enum DecodeOutcome {
+    /// Sender is registered; the message was verified and decrypted
+    Known {
+        sender: PublicIdentity,
+        content: String,
+    },
+    /// Sender is not registered; the signature is valid but the message cannot
+    /// be decrypted without the sender's exchange key
+    UnknownSender { sender_key: [u8; 32] },
+}
+
+/// Kind of recipient a message is planned for
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Recipient<'a> {
+    /// A single agent
+    Direct,
+    /// A group; its name travels in the payload metadata
+    Group(&'a str),
+}
+
+/// Planned size of a hidden message and the cover text it needs
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub struct CoverPlan {
+    /// Serialized message size in bytes
+    pub message_len: usize,
+    #[serde(flatten)]
+    pub requirement: CoverRequirement,
+}
+
+/// Exact serialized size of a message carrying `secret_len` bytes of content
+pub fn message_len(secret_len: usize, recipient: Recipient) -> usize {
+    let metadata = match recipient {
+        Recipient::Direct => None,
+        Recipient::Group(name) => Some(name),
+    };
+    HEADER_SIZE + EncryptedPayload::encoded_len(secret_len, metadata) + crypto::TAG_SIZE + crypto::SIGNATURE_SIZE
+}
+
+/// Plan a message of `secret_len` bytes for a recipient and carrier
+pub fn plan(codec: &dyn StegoCodec, secret_len: usize, recipient: Recipient) -> CoverPlan {
+    plan_message(codec, message_len(secret_len, recipient))
+}
+
+fn plan_message(codec: &dyn StegoCodec, message_len: usize) -> CoverPlan {
+    CoverPlan {
+        message_len,
+        requirement: codec.requirement(message_len),
+    }
+}
+
+/// Extra message bytes taken by cover binding
+fn cover_hash_len(bind_cover: bool) -> usize {
+    if bind_cover {
+        COVER_HASH_SIZE
+    } else {
+        0
     }
 }
 
@@ -186,6 +662,11 @@ impl WaterscapeChannel {
 pub struct Waterscape;
 
 impl Waterscape {
+    /// Plan the cover text needed by [`encode`](Self::encode) for a secret of `secret_len` bytes
+    pub fn plan(secret_len: usize) -> CoverPlan {
+        plan(&ZeroWidthCodec::default(), secret_len, Recipient::Direct)
+    }
+
     /// Encode a secret message for a specific recipient
     pub fn encode(
         sender: &Agent,
@@ -193,23 +674,134 @@ impl Waterscape {
         cover_text: &str,
         secret: &str,
     ) -> Result<String> {
-        let (channel, _) = WaterscapeChannel::establish(sender, recipient)?;
+        let channel = WaterscapeChannel::establish(sender, recipient)?;
+        channel.encode(sender, cover_text, secret)
+    }
+
+    /// Encode a secret message bound to its cover text
+    ///
+    /// The receiver learns from [`decode_verified`](Self::decode_verified) whether
+    /// the visible text is still the one the message was sent in.
+    pub fn encode_bound(
+        sender: &Agent,
+        recipient: &PublicIdentity,
+        cover_text: &str,
+        secret: &str,
+    ) -> Result<String> {
+        let channel = WaterscapeChannel::establish(sender, recipient)?.with_cover_binding(true);
+        channel.encode(sender, cover_text, secret)
+    }
+
+    /// Encode a secret message for a specific recipient using a specific carrier
+    pub fn encode_with_codec(
+        codec: impl StegoCodec + 'static,
+        sender: &Agent,
+        recipient: &PublicIdentity,
+        cover_text: &str,
+        secret: &str,
+    ) -> Result<String> {
+        let channel = WaterscapeChannel::establish(sender, recipient)?.with_codec(codec);
         channel.encode(sender, cover_text, secret)
     }
 
+    /// Decode a message hidden with a specific carrier (requires knowing the sender)
+    pub fn decode_with_codec(
+        codec: impl StegoCodec + 'static,
+        receiver: &Agent,
+        sender: &PublicIdentity,
+        text: &str,
+    ) -> Result<String> {
+        let channel = WaterscapeChannel::establish(receiver, sender)?.with_codec(codec);
+        channel.decode(receiver, text)
+    }
+
     /// Decode a message (requires knowing the sender)
+    ///
+    /// The carrier is detected automatically.
     pub fn decode(
         receiver: &Agent,
         sender: &PublicIdentity,
         text: &str,
     ) -> Result<String> {
-        // First extract the message to get the ephemeral key
+        let message = Self::extract_message(text)?;
+        let channel = WaterscapeChannel::establish(receiver, sender)?;
+        Ok(channel.decrypt_message(receiver, &message)?.content)
+    }
+
+    /// Decode a message that may come from a version 1 release
+    ///
+    /// Like [`decode`](Self::decode), but also accepts version 1 messages,
+    /// whose signature does not cover the header.
+    pub fn decode_legacy(
+        receiver: &Agent,
+        sender: &PublicIdentity,
+        text: &str,
+    ) -> Result<String> {
+        let message = Self::extract_message(text)?;
+        let channel = WaterscapeChannel::establish(receiver, sender)?.with_legacy_messages(true);
+        Ok(channel.decrypt_message(receiver, &message)?.content)
+    }
+
+    /// Decode a message and check it against the visible text it arrived in
+    ///
+    /// The carrier is detected automatically.
+    pub fn decode_verified(
+        receiver: &Agent,
+        sender: &PublicIdentity,
+        text: &str,
+    ) -> Result<DecodedMessage> {
+        let message = Self::extract_message(text)?;
+        let channel = WaterscapeChannel::establish(receiver, sender)?;
+        let payload = channel.decrypt_message(receiver, &message)?;
+        Ok(DecodedMessage::new(&message, sender.clone(), payload, &Self::visible_text(text)))
+    }
+
+    /// Decode a message, rejecting stale or already seen messages
+    pub fn decode_with_guard(
+        receiver: &Agent,
+        sender: &PublicIdentity,
+        text: &str,
+        guard: &mut ReplayGuard,
+    ) -> Result<String> {
+        let message = Self::extract_message(text)?;
+        let channel = WaterscapeChannel::establish(receiver, sender)?;
+        let payload = channel.decrypt_message(receiver, &message)?;
+        guard.check(message.id(), payload.timestamp)?;
+        Ok(payload.content)
+    }
+
+    /// Decode a message from any sender, resolving the sender by its signing key
+    pub fn decode_from_registry(
+        receiver: &Agent,
+        registry: &AgentRegistry,
+        text: &str,
+    ) -> Result<DecodeOutcome> {
+        let message = Self::extract_message(text)?;
+
+        let sender = match registry.get_by_signing_key(&message.sender_key) {
+            Some(sender) => sender,
+            None => {
+                message.check_version(false)?;
+                message.verify_signature(&receiver.public_identity().exchange_key)?;
+                return Ok(DecodeOutcome::UnknownSender {
+                    sender_key: message.sender_key,
+                });
+            }
+        };
+
+        let channel = WaterscapeChannel::establish(receiver, sender)?;
+        let payload = channel.decrypt_message(receiver, &message)?;
+
+        Ok(DecodeOutcome::Known {
+            sender: sender.clone(),
+            content: payload.content,
+        })
+    }
+
+    /// Extract a message from text in any supported carrier
+    fn extract_message(text: &str) -> Result<WaterscapeMessage> {
         let message_bytes = stego::extract_from_text(text)?;
-        let message = WaterscapeMessage::from_bytes(&message_bytes)?;
-        
-        // Establish channel with sender's ephemeral key
-        let channel = WaterscapeChannel::establish_receiver(receiver, sender, &message.ephemeral_key)?;
-        channel.decrypt_message(&message)
+        WaterscapeMessage::from_bytes(&message_bytes)
     }
 
     /// Check if text contains a hidden message
@@ -223,76 +815,586 @@ impl Waterscape {
     }
 }
 
+/// What a group member may do
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GroupRole {
+    /// Posts, and manages members, roles and keys
+    Admin,
+    /// Posts and reads
+    #[default]
+    Member,
+    /// Reads only; messages it sends are rejected
+    ReadOnly,
+}
+
+impl GroupRole {
+    /// Whether messages from this role are accepted
+    pub fn can_post(self) -> bool {
+        self != Self::ReadOnly
+    }
+
+    /// Byte signed for this role in envelope transcripts
+    fn code(self) -> u8 {
+        match self {
+            Self::Admin => 0,
+            Self::Member => 1,
+            Self::ReadOnly => 2,
+        }
+    }
+}
+
+/// A member of a group and its role
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GroupMember {
+    #[serde(flatten)]
+    pub identity: PublicIdentity,
+    #[serde(default)]
+    pub role: GroupRole,
+}
+
+impl GroupMember {
+    pub fn new(identity: PublicIdentity, role: GroupRole) -> Self {
+        Self { identity, role }
+    }
+}
+
+fn find_member<'a>(members: &'a [GroupMember], signing_key: &[u8; 32]) -> Option<&'a GroupMember> {
+    members.iter().find(|m| &m.identity.signing_key == signing_key)
+}
+
+/// The group key of a [`WaterscapeGroup`], encrypted for one member
+///
+/// The key is wrapped with a fresh ephemeral X25519 key combined with the static
+/// exchange keys of the sender and the member, exactly like a direct message.
+/// The sender signs every field, including the member list and roles, so a
+/// member who opens the envelope rebuilds the same group the sender has.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct GroupKeyEnvelope {
+    pub version: u8,
+    pub group_name: String,
+    /// Epoch the wrapped key belongs to
+    #[serde(default)]
+    pub epoch: u32,
+    pub members: Vec<GroupMember>,
+    /// Signing key of the admin who issued the envelope
+    #[serde(with = "hex::serde")]
+    pub sender_key: [u8; 32],
+    /// Exchange key of the member the envelope is for
+    #[serde(with = "hex::serde")]
+    pub recipient_key: [u8; 32],
+    #[serde(with = "hex::serde")]
+    pub ephemeral_key: [u8; 32],
+    #[serde(with = "hex::serde")]
+    pub nonce: [u8; NONCE_SIZE],
+    #[serde(with = "hex::serde")]
+    pub encrypted_key: Vec<u8>,
+    #[serde(with = "hex::serde")]
+    pub signature: Vec<u8>,
+}
+
+impl GroupKeyEnvelope {
+    /// Serialize to JSON bytes
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        serde_json::to_vec(self).map_err(|e| WaterscapeError::Serialization(e.to_string()))
+    }
+
+    /// Deserialize from JSON bytes
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let envelope: Self = serde_json::from_slice(bytes)?;
+        if envelope.version != ENVELOPE_VERSION {
+            return Err(WaterscapeError::VersionMismatch {
+                expected: ENVELOPE_VERSION,
+                got: envelope.version,
+            });
+        }
+        Ok(envelope)
+    }
+
+    /// Canonical bytes signed by the sender
+    fn transcript(&self) -> Vec<u8> {
+        let mut transcript = CONTEXT_ENVELOPE_SIGN.to_vec();
+        transcript.push(self.version);
+        push_str(&mut transcript, &self.group_name);
+        transcript.extend_from_slice(&self.epoch.to_be_bytes());
+        transcript.extend_from_slice(&(self.members.len() as u32).to_be_bytes());
+        for member in &self.members {
+            push_str(&mut transcript, &member.identity.name);
+            transcript.extend_from_slice(&member.identity.signing_key);
+            transcript.extend_from_slice(&member.identity.exchange_key);
+            transcript.push(member.role.code());
+        }
+        transcript.extend_from_slice(&self.sender_key);
+        transcript.extend_from_slice(&self.recipient_key);
+        transcript.extend_from_slice(&self.ephemeral_key);
+        transcript.extend_from_slice(&self.nonce);
+        transcript.extend_from_slice(&self.encrypted_key);
+        transcript
+    }
+
+    /// Key that wraps the group key for the recipient
+    fn wrapping_key(
+        ephemeral_secret: &SharedSecret,
+        static_secret: &SharedSecret,
+        ephemeral_key: &[u8; 32],
+        recipient_key: &[u8; 32],
+    ) -> [u8; KEY_SIZE] {
+        let mut context = Vec::with_capacity(CONTEXT_ENVELOPE.len() + 64);
+        context.extend_from_slice(CONTEXT_ENVELOPE);
+        context.extend_from_slice(ephemeral_key);
+        context.extend_from_slice(recipient_key);
+        ephemeral_secret.derive_key_with(static_secret, &context)
+    }
+
+    /// Verify the envelope and unwrap its key
+    ///
+    /// The sender must be an admin in `senders`: the envelope's own member list
+    /// for a new group, the current members for a re-key of an existing one.
+    fn open(&self, member: &Agent, senders: &[GroupMember]) -> Result<[u8; KEY_SIZE]> {
+        let identity = member.public_identity();
+        if self.recipient_key != identity.exchange_key {
+            return Err(WaterscapeError::KeyExchange("Envelope is addressed to another agent".into()));
+        }
+        if find_member(&self.members, &identity.signing_key).is_none() {
+            return Err(WaterscapeError::Unauthorized);
+        }
+        if !self.members.iter().any(|m| m.role == GroupRole::Admin) {
+            return Err(WaterscapeError::Membership("A group needs at least one admin".into()));
+        }
+
+        // Only an admin of the group may hand out its key
+        let sender = find_member(senders, &self.sender_key).ok_or(WaterscapeError::AuthenticationFailed)?;
+        if sender.role != GroupRole::Admin {
+            return Err(WaterscapeError::Unauthorized);
+        }
+        let sender = &sender.identity;
+        let signature: [u8; 64] = self.signature.clone().try_into()
+            .map_err(|_| WaterscapeError::Crypto("Invalid signature length".into()))?;
+        crypto::verify_signature(&sender.signing_key, &self.transcript(), &Signature::from_bytes(&signature))?;
+
+        let exchange = member.exchange_keypair();
+        let ephemeral_secret = exchange.diffie_hellman(&X25519PublicKey::from(self.ephemeral_key));
+        let static_secret = exchange.diffie_hellman(&X25519PublicKey::from(sender.exchange_key));
+        let mut key = Self::wrapping_key(&ephemeral_secret, &static_secret, &self.ephemeral_key, &self.recipient_key);
+        let group_key = crypto::decrypt(&key, &self.nonce, &self.encrypted_key);
+        key.zeroize();
+        let mut group_key = group_key?;
+
+        let result = <[u8; KEY_SIZE]>::try_from(group_key.as_slice())
+            .map_err(|_| WaterscapeError::Crypto("Invalid group key length".into()));
+        group_key.zeroize();
+        result
+    }
+}
+
+/// Append a length-prefixed string to a transcript
+pub(crate) fn push_str(transcript: &mut Vec<u8>, value: &str) {
+    transcript.extend_from_slice(&(value.len() as u32).to_be_bytes());
+    transcript.extend_from_slice(value.as_bytes());
+}
+
+/// Public identifier of a group key, signed as the recipient of group messages
+pub(crate) fn group_id(group_key: &[u8; KEY_SIZE]) -> [u8; 32] {
+    crypto::derive_from_seed(group_key, CONTEXT_GROUP_ID)
+}
+
+/// Group messages carry their epoch in the otherwise unused ephemeral key field
+fn epoch_tag(epoch: u32) -> [u8; 32] {
+    let mut tag = [0u8; 32];
+    tag[..4].copy_from_slice(&epoch.to_be_bytes());
+    tag
+}
+
+fn epoch_of(message: &WaterscapeMessage) -> Result<u32> {
+    let (epoch, rest) = message.ephemeral_key.split_at(4);
+    if rest.iter().any(|&b| b != 0) {
+        return Err(WaterscapeError::Decoding("Malformed group epoch".into()));
+    }
+    Ok(u32::from_be_bytes(epoch.try_into().expect("split at 4 bytes")))
+}
+
+/// Key and member list of one group epoch
+struct GroupEpoch {
+    key: [u8; KEY_SIZE],
+    members: Vec<GroupMember>,
+}
+
+impl Drop for GroupEpoch {
+    fn drop(&mut self) {
+        self.key.zeroize();
+    }
+}
+
 /// Group channel for multiple agents
+///
+/// The creator generates a random group key and hands it to the other members
+/// in [`GroupKeyEnvelope`]s; each member opens its envelope with
+/// [`from_envelope`](Self::from_envelope) to build the same group.
+///
+/// Every membership or role change moves the group to a new epoch with a fresh
+/// key, which is sent only to the current members. Keys and member lists of
+/// earlier epochs are kept so members can still read the history they were part
+/// of. Messages are only accepted from senders who could post in their epoch.
 pub struct WaterscapeGroup {
     name: String,
-    members: Vec<PublicIdentity>,
-    group_key: [u8; KEY_SIZE],
+    epoch: u32,
+    epochs: BTreeMap<u32, GroupEpoch>,
+    codec: Box<dyn StegoCodec>,
+    bind_cover: bool,
 }
 
 impl WaterscapeGroup {
-    /// Create a new group with a shared secret
+    /// Create a new group with a fresh random key
+    ///
+    /// The creator is the group's admin and is added to the members if
+    /// missing; everyone else joins as a [`GroupRole::Member`]. Use
+    /// [`envelopes`](Self::envelopes) to hand the key to the other members.
     pub fn new(name: &str, creator: &Agent, members: Vec<PublicIdentity>) -> Self {
-        // Generate group key from creator's signing key + group name
-        use sha2::{Sha256, Digest};
-        let mut hasher = Sha256::new();
-        hasher.update(creator.export_signing_key());
-        hasher.update(name.as_bytes());
-        let result = hasher.finalize();
-        
-        let mut group_key = [0u8; KEY_SIZE];
-        group_key.copy_from_slice(&result);
+        let creator_identity = creator.public_identity();
+        let mut members: Vec<GroupMember> = members
+            .into_iter()
+            .map(|identity| {
+                let role = if identity.signing_key == creator_identity.signing_key {
+                    GroupRole::Admin
+                } else {
+                    GroupRole::Member
+                };
+                GroupMember::new(identity, role)
+            })
+            .collect();
+        if find_member(&members, &creator_identity.signing_key).is_none() {
+            members.insert(0, GroupMember::new(creator_identity, GroupRole::Admin));
+        }
 
+        Self::with_key(name, members, 0, crypto::generate_key())
+    }
+
+    /// Open a key envelope and build the group it describes
+    pub fn from_envelope(member: &Agent, envelope: &GroupKeyEnvelope) -> Result<Self> {
+        let key = envelope.open(member, &envelope.members)?;
+        Ok(Self::with_key(&envelope.group_name, envelope.members.clone(), envelope.epoch, key))
+    }
+
+    pub(crate) fn with_key(name: &str, members: Vec<GroupMember>, epoch: u32, key: [u8; KEY_SIZE]) -> Self {
         Self {
             name: name.to_string(),
-            members,
-            group_key,
+            epoch,
+            epochs: BTreeMap::from([(epoch, GroupEpoch { key, members })]),
+            codec: Box::new(ZeroWidthCodec::default()),
+            bind_cover: false,
+        }
+    }
+
+    /// Use a different steganographic carrier for this group
+    pub fn with_codec(mut self, codec: impl StegoCodec + 'static) -> Self {
+        self.codec = Box::new(codec);
+        self
+    }
+
+    /// Carrier used by this group
+    pub fn codec(&self) -> &dyn StegoCodec {
+        self.codec.as_ref()
+    }
+
+    /// Bind every message to the visible text it is sent in
+    pub fn with_cover_binding(mut self, bind: bool) -> Self {
+        self.bind_cover = bind;
+        self
+    }
+
+    /// State of the current epoch
+    fn current(&self) -> &GroupEpoch {
+        &self.epochs[&self.epoch]
+    }
+
+    /// Role of a current member
+    pub fn role(&self, signing_key: &[u8; 32]) -> Option<GroupRole> {
+        find_member(self.members(), signing_key).map(|m| m.role)
+    }
+
+    fn check_admin(&self, agent: &Agent) -> Result<()> {
+        match self.role(&agent.public_identity().signing_key) {
+            Some(GroupRole::Admin) => Ok(()),
+            _ => Err(WaterscapeError::Unauthorized),
+        }
+    }
+
+    /// Move to a new epoch with a fresh key, returning its envelopes for the
+    /// other current members
+    pub fn rotate(&mut self, sender: &Agent) -> Result<Vec<GroupKeyEnvelope>> {
+        let members = self.members().to_vec();
+        self.rotate_with(sender, members)
+    }
+
+    /// Move to a new epoch with a fresh key and a new member list
+    fn rotate_with(&mut self, sender: &Agent, members: Vec<GroupMember>) -> Result<Vec<GroupKeyEnvelope>> {
+        self.check_admin(sender)?;
+        let epoch = self
+            .epoch
+            .checked_add(1)
+            .ok_or_else(|| WaterscapeError::Membership("Group epoch exhausted".into()))?;
+        self.epochs.insert(epoch, GroupEpoch { key: crypto::generate_key(), members });
+        self.epoch = epoch;
+        self.envelopes(sender)
+    }
+
+    /// Add a member with a role and re-key; the new member cannot read earlier epochs
+    pub fn add_member(
+        &mut self,
+        sender: &Agent,
+        member: PublicIdentity,
+        role: GroupRole,
+    ) -> Result<Vec<GroupKeyEnvelope>> {
+        self.check_admin(sender)?;
+        if find_member(self.members(), &member.signing_key).is_some() {
+            return Err(WaterscapeError::Membership(format!("'{}' is already a member", member.name)));
+        }
+        let mut members = self.members().to_vec();
+        members.push(GroupMember::new(member, role));
+        self.rotate_with(sender, members)
+    }
+
+    /// Remove a member and re-key; the removed member keeps only earlier epochs
+    pub fn remove_member(&mut self, sender: &Agent, signing_key: &[u8; 32]) -> Result<Vec<GroupKeyEnvelope>> {
+        self.check_admin(sender)?;
+        if &sender.public_identity().signing_key == signing_key {
+            return Err(WaterscapeError::Membership("A member cannot remove itself".into()));
+        }
+        let mut members = self.members().to_vec();
+        let index = members
+            .iter()
+            .position(|m| &m.identity.signing_key == signing_key)
+            .ok_or_else(|| WaterscapeError::Membership("Not a member of this group".into()))?;
+        members.remove(index);
+        self.rotate_with(sender, members)
+    }
+
+    /// Change a member's role and re-key, so every member learns the new roles
+    pub fn set_role(
+        &mut self,
+        sender: &Agent,
+        signing_key: &[u8; 32],
+        role: GroupRole,
+    ) -> Result<Vec<GroupKeyEnvelope>> {
+        self.check_admin(sender)?;
+        let mut members = self.members().to_vec();
+        let member = members
+            .iter_mut()
+            .find(|m| &m.identity.signing_key == signing_key)
+            .ok_or_else(|| WaterscapeError::Membership("Not a member of this group".into()))?;
+        member.role = role;
+        if !members.iter().any(|m| m.role == GroupRole::Admin) {
+            return Err(WaterscapeError::Membership("A group needs at least one admin".into()));
         }
+        self.rotate_with(sender, members)
+    }
+
+    /// Apply a re-key envelope from a current admin
+    ///
+    /// Newer epochs replace the member list; a redelivered envelope is ignored.
+    /// Only one admin may re-key from a given epoch: an envelope carrying a
+    /// different key for an epoch already known fails with
+    /// [`WaterscapeError::Membership`], and the losing admin has to re-key again
+    /// from the epoch that was kept.
+    pub fn apply_envelope(&mut self, member: &Agent, envelope: &GroupKeyEnvelope) -> Result<()> {
+        if envelope.group_name != self.name {
+            return Err(WaterscapeError::KeyExchange("Envelope is for another group".into()));
+        }
+        let key = envelope.open(member, self.members())?;
+        self.store_epoch(envelope.epoch, key, envelope.members.clone())
+    }
+
+    /// Keep the key of an epoch, moving to it if newer
+    fn store_epoch(&mut self, epoch: u32, key: [u8; KEY_SIZE], members: Vec<GroupMember>) -> Result<()> {
+        match self.epochs.get(&epoch) {
+            Some(known) if known.key != key => {
+                return Err(WaterscapeError::Membership(format!("Conflicting key for epoch {}", epoch)));
+            }
+            Some(_) => {}
+            None => {
+                self.epochs.insert(epoch, GroupEpoch { key, members });
+            }
+        }
+        self.epoch = self.epoch.max(epoch);
+        Ok(())
+    }
+
+    /// Current epoch
+    pub fn epoch(&self) -> u32 {
+        self.epoch
+    }
+
+    /// Encrypt the current group key for one member
+    ///
+    /// Only admins hand out the key.
+    pub fn envelope_for(&self, sender: &Agent, member: &PublicIdentity) -> Result<GroupKeyEnvelope> {
+        self.check_admin(sender)?;
+        if find_member(self.members(), &member.signing_key).is_none() {
+            return Err(WaterscapeError::Unauthorized);
+        }
+
+        let ephemeral = EphemeralKeyPair::generate();
+        let ephemeral_key = ephemeral.public_key_bytes();
+        let member_key = X25519PublicKey::from(member.exchange_key);
+        let ephemeral_secret = ephemeral.diffie_hellman(&member_key);
+        let static_secret = sender.exchange_keypair().diffie_hellman(&member_key);
+        let mut key = GroupKeyEnvelope::wrapping_key(
+            &ephemeral_secret,
+            &static_secret,
+            &ephemeral_key,
+            &member.exchange_key,
+        );
+        let nonce = crypto::generate_nonce();
+        let encrypted_key = crypto::encrypt(&key, &nonce, &self.current().key);
+        key.zeroize();
+
+        let mut envelope = GroupKeyEnvelope {
+            version: ENVELOPE_VERSION,
+            group_name: self.name.clone(),
+            epoch: self.epoch,
+            members: self.members().to_vec(),
+            sender_key: sender.public_identity().signing_key,
+            recipient_key: member.exchange_key,
+            ephemeral_key,
+            nonce,
+            encrypted_key: encrypted_key?,
+            signature: Vec::new(),
+        };
+        envelope.signature = sender.signing_keypair().sign(&envelope.transcript()).to_bytes().to_vec();
+        Ok(envelope)
+    }
+
+    /// Encrypt the group key for every member except the sender
+    pub fn envelopes(&self, sender: &Agent) -> Result<Vec<GroupKeyEnvelope>> {
+        let sender_key = sender.public_identity().signing_key;
+        self.members()
+            .iter()
+            .filter(|m| m.identity.signing_key != sender_key)
+            .map(|m| self.envelope_for(sender, &m.identity))
+            .collect()
+    }
+
+    /// Plan the cover text needed for a secret of `secret_len` bytes
+    pub fn plan(&self, secret_len: usize) -> CoverPlan {
+        let message_len = message_len(secret_len, Recipient::Group(&self.name)) + cover_hash_len(self.bind_cover);
+        plan_message(self.codec(), message_len)
     }
 
     /// Encode message for the group
+    ///
+    /// The sender must be a current member allowed to post.
     pub fn encode(&self, sender: &Agent, cover_text: &str, secret: &str) -> Result<String> {
+        match self.role(&sender.public_identity().signing_key) {
+            Some(role) if role.can_post() => {}
+            _ => return Err(WaterscapeError::Unauthorized),
+        }
+
         let nonce = crypto::generate_nonce();
 
         let payload = EncryptedPayload {
             content: secret.to_string(),
             timestamp: unix_timestamp_secs(),
             metadata: Some(self.name.clone()),
+            cover_hash: self.bind_cover.then(|| cover_digest(cover_text)),
         };
 
-        let payload_bytes = serde_json::to_vec(&payload)?;
-        let ciphertext = crypto::encrypt(&self.group_key, &nonce, &payload_bytes)?;
-        let signature = sender.signing_keypair().sign(&ciphertext);
+        let payload_bytes = payload.to_bytes()?;
+        let group_key = &self.current().key;
+        let ciphertext = crypto::encrypt(group_key, &nonce, &payload_bytes)?;
 
-        let message = WaterscapeMessage {
+        let mut message = WaterscapeMessage {
             version: PROTOCOL_VERSION,
             nonce,
             sender_key: sender.public_identity().signing_key,
-            ephemeral_key: [0u8; 32], // Not used for group messages
+            ephemeral_key: epoch_tag(self.epoch),
             ciphertext,
-            signature: signature.to_bytes().to_vec(),
+            signature: Vec::new(),
         };
+        let signature = sender.signing_keypair().sign(&message.transcript(&group_id(group_key)));
+        message.signature = signature.to_bytes().to_vec();
+
+        let mut context = CONTEXT_PLACEMENT.to_vec();
+        context.extend_from_slice(&nonce);
+        let mut placement_key = crypto::derive_from_seed(group_key, &context);
 
         let message_bytes = message.to_bytes()?;
-        stego::hide_in_text(cover_text, &message_bytes)
+        let encoded = self.codec.hide_keyed(cover_text, &message_bytes, &placement_key);
+        placement_key.zeroize();
+        encoded
     }
 
     /// Decode group message
     pub fn decode(&self, text: &str) -> Result<String> {
-        let message_bytes = stego::extract_from_text(text)?;
+        let message_bytes = self.codec.extract(text)?;
         let message = WaterscapeMessage::from_bytes(&message_bytes)?;
+        Ok(self.decrypt_message(&message)?.1.content)
+    }
 
-        // Verify signature
-        let sig_bytes: [u8; 64] = message.signature.clone().try_into()
-            .map_err(|_| WaterscapeError::Crypto("Invalid signature length".into()))?;
-        let signature = Signature::from_bytes(&sig_bytes);
-        crypto::verify_signature(&message.sender_key, &message.ciphertext, &signature)?;
+    /// Decode group message together with its sender, and check it against the
+    /// visible text it arrived in
+    pub fn decode_verified(&self, text: &str) -> Result<DecodedMessage> {
+        let message_bytes = self.codec.extract(text)?;
+        let message = WaterscapeMessage::from_bytes(&message_bytes)?;
+        let (sender, payload) = self.decrypt_message(&message)?;
+        Ok(DecodedMessage::new(&message, sender.identity.clone(), payload, &self.codec.strip(text)))
+    }
+
+    /// Decode group message, rejecting stale or already seen messages
+    pub fn decode_with_guard(&self, text: &str, guard: &mut ReplayGuard) -> Result<String> {
+        let message_bytes = self.codec.extract(text)?;
+        let message = WaterscapeMessage::from_bytes(&message_bytes)?;
+        let (_, payload) = self.decrypt_message(&message)?;
+        guard.check(message.id(), payload.timestamp)?;
+        Ok(payload.content)
+    }
+
+    /// Verify and decrypt a group message, resolving its sender
+    fn decrypt_message(&self, message: &WaterscapeMessage) -> Result<(&GroupMember, EncryptedPayload)> {
+        // Verify version and signature under the key of the message's epoch.
+        // Version 1 group keys were never shared with members, so there is no
+        // version 1 traffic a group could read.
+        message.check_version(false)?;
+        let epoch = epoch_of(message)?;
+        let group_key = self.epoch_key(epoch)?;
+        message.verify_signature(&group_id(group_key))?;
+
+        // Holding the key is not enough: the sender must have been able to post then
+        let sender = self.authorized_sender(epoch, &message.sender_key)?;
 
         // Decrypt with group key
-        let payload_bytes = crypto::decrypt(&self.group_key, &message.nonce, &message.ciphertext)?;
-        let payload: EncryptedPayload = serde_json::from_slice(&payload_bytes)?;
+        let payload_bytes = crypto::decrypt(group_key, &message.nonce, &message.ciphertext)?;
+        let payload = EncryptedPayload::from_bytes(&payload_bytes)?;
 
-        Ok(payload.content)
+        Ok((sender, payload))
+    }
+
+    /// Record an epoch agreed outside this group's own re-keying, moving to it if newer
+    pub(crate) fn insert_epoch(&mut self, epoch: u32, key: [u8; KEY_SIZE], members: Vec<GroupMember>) -> Result<()> {
+        self.store_epoch(epoch, key, members)
+    }
+
+    /// Key of an epoch this member received
+    pub(crate) fn epoch_key(&self, epoch: u32) -> Result<&[u8; KEY_SIZE]> {
+        self.epochs
+            .get(&epoch)
+            .map(|e| &e.key)
+            .ok_or(WaterscapeError::UnknownEpoch { epoch })
+    }
+
+    /// Member who was allowed to post in an epoch
+    pub(crate) fn authorized_sender(&self, epoch: u32, signing_key: &[u8; 32]) -> Result<&GroupMember> {
+        let members = self.epochs.get(&epoch).map_or(&[][..], |e| &e.members);
+        match find_member(members, signing_key) {
+            Some(sender) if sender.role.can_post() => Ok(sender),
+            _ => Err(WaterscapeError::SenderNotPermitted {
+                sender: hex::encode(&signing_key[..8]),
+            }),
+        }
+    }
+
+    /// Whether messages are bound to their cover text
+    pub(crate) fn binds_cover(&self) -> bool {
+        self.bind_cover
     }
 
     /// Get group name
@@ -300,15 +1402,16 @@ impl WaterscapeGroup {
         &self.name
     }
 
-    /// List members
-    pub fn members(&self) -> &[PublicIdentity] {
-        &self.members
+    /// List current members and their roles
+    pub fn members(&self) -> &[GroupMember] {
+        &self.current().members
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::replay::DEFAULT_MAX_AGE_SECS;
 
     #[test]
     fn test_encode_decode() {
@@ -345,6 +1448,611 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_fresh_ephemeral_key_per_message() {
+        let alice = Agent::new("alice");
+        let bob = Agent::new("bob");
+        let channel = WaterscapeChannel::establish(&alice, &bob.public_identity()).unwrap();
+
+        let first = channel.encode(&alice, "First cover text.", "one").unwrap();
+        let second = channel.encode(&alice, "Second cover text.", "two").unwrap();
+
+        let first_message = WaterscapeMessage::from_bytes(&stego::extract_from_text(&first).unwrap()).unwrap();
+        let second_message = WaterscapeMessage::from_bytes(&stego::extract_from_text(&second).unwrap()).unwrap();
+
+        // Neither message exposes the long-lived exchange key, and each uses its own ephemeral key
+        let alice_exchange_key = alice.public_identity().exchange_key;
+        assert_ne!(first_message.ephemeral_key, alice_exchange_key);
+        assert_ne!(second_message.ephemeral_key, alice_exchange_key);
+        assert_ne!(first_message.ephemeral_key, second_message.ephemeral_key);
+
+        // Bob decodes both through his side of the channel
+        let bob_channel = WaterscapeChannel::establish(&bob, &alice.public_identity()).unwrap();
+        assert_eq!(bob_channel.decode(&bob, &first).unwrap(), "one");
+        assert_eq!(bob_channel.decode(&bob, &second).unwrap(), "two");
+    }
+
+    #[test]
+    fn test_wrong_sender_rejected() {
+        let alice = Agent::new("alice");
+        let bob = Agent::new("bob");
+        let mallory = Agent::new("mallory");
+
+        let encoded = Waterscape::encode(&alice, &bob.public_identity(), "Hi there!", "secret").unwrap();
+
+        // Bob expects the message to come from Mallory
+        let result = Waterscape::decode(&bob, &mallory.public_identity(), &encoded);
+        assert!(matches!(result, Err(WaterscapeError::AuthenticationFailed)));
+    }
+
+    #[test]
+    fn test_restored_agent_reads_inbox() {
+        let alice = Agent::new("alice");
+        let bob = Agent::new("bob");
+
+        let encoded = Waterscape::encode(&alice, &bob.public_identity(), "See you later!", "inbox").unwrap();
+
+        // Bob restarts from his backup
+        let restored_bob = Agent::from_backup(&bob.export_backup()).unwrap();
+        drop(bob);
+
+        let decoded = Waterscape::decode(&restored_bob, &alice.public_identity(), &encoded).unwrap();
+        assert_eq!(decoded, "inbox");
+    }
+
+    #[test]
+    fn test_replayed_message_rejected() {
+        let alice = Agent::new("alice");
+        let bob = Agent::new("bob");
+        let mut guard = ReplayGuard::default();
+
+        let encoded = Waterscape::encode(&alice, &bob.public_identity(), "Great post!", "act now").unwrap();
+
+        let decoded = Waterscape::decode_with_guard(&bob, &alice.public_identity(), &encoded, &mut guard).unwrap();
+        assert_eq!(decoded, "act now");
+
+        // A bot re-posting the same comment must not trigger the action again
+        let replayed = Waterscape::decode_with_guard(&bob, &alice.public_identity(), &encoded, &mut guard);
+        assert!(matches!(replayed, Err(WaterscapeError::ReplayedMessage)));
+    }
+
+    #[test]
+    fn test_stale_message_rejected() {
+        let alice = Agent::new("alice");
+        let bob = Agent::new("bob");
+        let group = WaterscapeGroup::new("club", &alice, vec![alice.public_identity(), bob.public_identity()]);
+        let encoded = group.encode(&alice, "Old news.", "expired").unwrap();
+
+        // Read back one second past the freshness window
+        let later = unix_timestamp_secs() + DEFAULT_MAX_AGE_SECS + 1;
+        let mut guard = ReplayGuard::default().with_clock(move || later);
+        let result = group.decode_with_guard(&encoded, &mut guard);
+        assert!(matches!(result, Err(WaterscapeError::StaleMessage { .. })));
+    }
+
+    #[test]
+    fn test_decode_from_registry() {
+        let alice = Agent::new("alice");
+        let bob = Agent::new("bob");
+        let stranger = Agent::new("stranger");
+
+        let mut registry = AgentRegistry::new();
+        registry.register(alice.public_identity());
+
+        let from_alice = Waterscape::encode(&alice, &bob.public_identity(), "Morning!", "hello bob").unwrap();
+        match Waterscape::decode_from_registry(&bob, &registry, &from_alice).unwrap() {
+            DecodeOutcome::Known { sender, content } => {
+                assert_eq!(sender.name, "alice");
+                assert_eq!(content, "hello bob");
+            }
+            DecodeOutcome::UnknownSender { .. } => panic!("Alice should be known"),
+        }
+
+        let from_stranger = Waterscape::encode(&stranger, &bob.public_identity(), "Hey!", "who am i").unwrap();
+        match Waterscape::decode_from_registry(&bob, &registry, &from_stranger).unwrap() {
+            DecodeOutcome::UnknownSender { sender_key } => {
+                assert_eq!(sender_key, stranger.public_identity().signing_key);
+            }
+            DecodeOutcome::Known { .. } => panic!("Stranger should be unknown"),
+        }
+    }
+
+    #[test]
+    fn test_binary_wire_format_is_compact() {
+        let alice = Agent::new("alice");
+        let bob = Agent::new("bob");
+        let channel = WaterscapeChannel::establish(&alice, &bob.public_identity()).unwrap();
+
+        let (message, _) = channel.create_message(&alice, "twenty character msg", None).unwrap();
+        let binary = message.to_bytes().unwrap();
+        let json = message.to_json_bytes().unwrap();
+
+        // header + (payload header + content + AEAD tag) + signature
+        assert_eq!(binary.len(), HEADER_SIZE + (10 + 20 + 16) + crypto::SIGNATURE_SIZE);
+        assert!(binary.len() * 2 < json.len());
+
+        let parsed = WaterscapeMessage::from_bytes(&binary).unwrap();
+        assert_eq!(parsed.nonce, message.nonce);
+        assert_eq!(parsed.ciphertext, message.ciphertext);
+        assert_eq!(parsed.signature, message.signature);
+    }
+
+    /// Agents whose keys produced the checked-in baseline fixtures
+    fn baseline_agents() -> (Agent, Agent) {
+        let alice = Agent::from_keys("alice", &[1u8; 32], &[2u8; 32]).unwrap();
+        let bob = Agent::from_keys("bob", &[3u8; 32], &[4u8; 32]).unwrap();
+        (alice, bob)
+    }
+
+    /// Direct message from Alice to Bob, hidden by the first release
+    const BASELINE_DIRECT: &str = include_str!("../tests/fixtures/v1_direct.txt");
+    /// Message to the first release's group "legacy-club", created by Alice
+    const BASELINE_GROUP: &str = include_str!("../tests/fixtures/v1_group.txt");
+
+    #[test]
+    fn test_legacy_json_message_accepted() {
+        let (alice, bob) = baseline_agents();
+
+        // The first release wrote version 1 JSON messages in binary zero-width text
+        let message = WaterscapeMessage::from_bytes(&stego::extract_from_text(BASELINE_DIRECT).unwrap()).unwrap();
+        assert_eq!(message.version, LEGACY_VERSION);
+        assert_eq!(Waterscape::visible_text(BASELINE_DIRECT), "Plain old text from the first release.");
+
+        let bob_channel = WaterscapeChannel::establish(&bob, &alice.public_identity())
+            .unwrap()
+            .with_legacy_messages(true);
+        assert_eq!(bob_channel.decode(&bob, BASELINE_DIRECT).unwrap(), "hello from version one");
+    }
+
+    #[test]
+    fn test_signature_covers_header() {
+        let alice = Agent::new("alice");
+        let bob = Agent::new("bob");
+        let carol = Agent::new("carol");
+        let channel = WaterscapeChannel::establish(&alice, &bob.public_identity()).unwrap();
+        let bob_channel = WaterscapeChannel::establish(&bob, &alice.public_identity()).unwrap();
+
+        let (message, _) = channel.create_message(&alice, "signed header", None).unwrap();
+        assert_eq!(message.version, PROTOCOL_VERSION);
+        assert!(message.verify_signature(&bob.public_identity().exchange_key).is_ok());
+        // Signed for Bob, so it does not verify as addressed to Carol
+        assert!(matches!(
+            message.verify_signature(&carol.public_identity().exchange_key),
+            Err(WaterscapeError::InvalidSignature)
+        ));
+
+        let mut reframed = WaterscapeMessage::from_bytes(&message.to_bytes().unwrap()).unwrap();
+        reframed.nonce[0] ^= 1;
+        assert!(matches!(bob_channel.decrypt_message(&bob, &reframed), Err(WaterscapeError::InvalidSignature)));
+
+        let mut reframed = WaterscapeMessage::from_bytes(&message.to_bytes().unwrap()).unwrap();
+        reframed.ephemeral_key = EphemeralKeyPair::generate().public_key_bytes();
+        assert!(matches!(bob_channel.decrypt_message(&bob, &reframed), Err(WaterscapeError::InvalidSignature)));
+
+        // A group message cannot be reposted in another group of the same sender
+        let members = vec![alice.public_identity(), bob.public_identity()];
+        let council = WaterscapeGroup::new("council", &alice, members.clone());
+        let other = WaterscapeGroup::new("other", &alice, members);
+        let encoded = council.encode(&alice, "Group cover text for everyone.", "hi").unwrap();
+        let message = WaterscapeMessage::from_bytes(&stego::extract_from_text(&encoded).unwrap()).unwrap();
+        assert!(message.verify_signature(&group_id(&other.current().key)).is_err());
+    }
+
+    #[test]
+    fn test_legacy_version_requires_opt_in() {
+        let (alice, bob) = baseline_agents();
+
+        assert!(matches!(
+            Waterscape::decode(&bob, &alice.public_identity(), BASELINE_DIRECT),
+            Err(WaterscapeError::VersionMismatch { expected: PROTOCOL_VERSION, got: LEGACY_VERSION })
+        ));
+        assert!(WaterscapeChannel::establish(&bob, &alice.public_identity())
+            .unwrap()
+            .decode(&bob, BASELINE_DIRECT)
+            .is_err());
+
+        // Every advertised opt-in reads the real version 1 message
+        assert_eq!(
+            Waterscape::decode_legacy(&bob, &alice.public_identity(), BASELINE_DIRECT).unwrap(),
+            "hello from version one"
+        );
+        let bob_channel = WaterscapeChannel::establish(&bob, &alice.public_identity())
+            .unwrap()
+            .with_legacy_messages(true);
+        assert_eq!(bob_channel.decode(&bob, BASELINE_DIRECT).unwrap(), "hello from version one");
+
+        // Current messages still decode through the legacy entry point
+        let encoded = Waterscape::encode(&alice, &bob.public_identity(), "Plain new text.", "from version two").unwrap();
+        assert_eq!(Waterscape::decode_legacy(&bob, &alice.public_identity(), &encoded).unwrap(), "from version two");
+
+        // Version 1 group keys only ever existed on the creator's side
+        let members = vec![alice.public_identity(), bob.public_identity()];
+        let group = WaterscapeGroup::new("legacy-club", &alice, members);
+        assert!(matches!(
+            group.decode(BASELINE_GROUP),
+            Err(WaterscapeError::VersionMismatch { expected: PROTOCOL_VERSION, got: LEGACY_VERSION })
+        ));
+    }
+
+    #[test]
+    fn test_payload_formats() {
+        let payload = EncryptedPayload {
+            content: "hello".to_string(),
+            timestamp: 1_700_000_000,
+            metadata: Some("group".to_string()),
+            cover_hash: Some([7u8; COVER_HASH_SIZE]),
+        };
+
+        let parsed = EncryptedPayload::from_bytes(&payload.to_bytes().unwrap()).unwrap();
+        assert_eq!(parsed.content, "hello");
+        assert_eq!(parsed.timestamp, 1_700_000_000);
+        assert_eq!(parsed.metadata.as_deref(), Some("group"));
+        assert_eq!(parsed.cover_hash, Some([7u8; COVER_HASH_SIZE]));
+
+        let legacy = EncryptedPayload::from_bytes(&serde_json::to_vec(&payload).unwrap()).unwrap();
+        assert_eq!(legacy.content, "hello");
+
+        let bytes = payload.to_bytes().unwrap();
+        assert!(EncryptedPayload::from_bytes(&bytes[..12]).is_err());
+    }
+
+    #[test]
+    fn test_channel_with_codec() {
+        use crate::stego::ZeroWidthEncoding;
+
+        let alice = Agent::new("alice");
+        let bob = Agent::new("bob");
+        let codec = ZeroWidthCodec::new(ZeroWidthEncoding::Binary);
+
+        let compact = Waterscape::encode(&alice, &bob.public_identity(), "Same cover.", "same secret").unwrap();
+        let encoded = Waterscape::encode_with_codec(codec, &alice, &bob.public_identity(), "Same cover.", "same secret").unwrap();
+        assert!(encoded.chars().count() > compact.chars().count());
+
+        let decoded = Waterscape::decode_with_codec(codec, &bob, &alice.public_identity(), &encoded).unwrap();
+        assert_eq!(decoded, "same secret");
+    }
+
+    #[test]
+    fn test_single_emoji_cover() {
+        use crate::stego::variation::VariationSelectorCodec;
+
+        let alice = Agent::new("alice");
+        let bob = Agent::new("bob");
+
+        let encoded = Waterscape::encode_with_codec(
+            VariationSelectorCodec,
+            &alice,
+            &bob.public_identity(),
+            "\u{1F44D}",
+            "agreed",
+        )
+        .unwrap();
+
+        assert_eq!(Waterscape::visible_text(&encoded), "\u{1F44D}");
+        assert!(Waterscape::has_hidden_message(&encoded));
+
+        // The high-level decoder detects the carrier on its own
+        let decoded = Waterscape::decode(&bob, &alice.public_identity(), &encoded).unwrap();
+        assert_eq!(decoded, "agreed");
+    }
+
+    #[test]
+    fn test_ascii_only_cover() {
+        use crate::stego::whitespace::WhitespaceCodec;
+
+        let alice = Agent::new("alice");
+        let bob = Agent::new("bob");
+        let cover = "ERROR retrying upload\nWARN backoff 2s\nINFO upload ok";
+
+        let encoded =
+            Waterscape::encode_with_codec(WhitespaceCodec, &alice, &bob.public_identity(), cover, "rotate keys").unwrap();
+
+        assert!(encoded.is_ascii());
+        assert_eq!(Waterscape::visible_text(&encoded), cover);
+        let decoded = Waterscape::decode(&bob, &alice.public_identity(), &encoded).unwrap();
+        assert_eq!(decoded, "rotate keys");
+    }
+
+    #[test]
+    fn test_fec_survives_edit() {
+        use crate::stego::fec::FecCodec;
+
+        let alice = Agent::new("alice");
+        let bob = Agent::new("bob");
+        let cover = "Thanks for the writeup! I tried the same approach last week and ran into \
+                     the exact issue you describe with the cache warming up too slowly.";
+
+        let encoded =
+            Waterscape::encode_with_codec(FecCodec::default(), &alice, &bob.public_identity(), cover, "fallback plan").unwrap();
+
+        // A moderator rewrites a few words in the middle of the post
+        let chars: Vec<char> = encoded.chars().collect();
+        let (start, end) = (chars.len() / 2, chars.len() / 2 + chars.len() / 10);
+        let edited: String = chars[..start].iter().chain(&chars[end..]).collect();
+
+        let decoded = Waterscape::decode(&bob, &alice.public_identity(), &edited).unwrap();
+        assert_eq!(decoded, "fallback plan");
+    }
+
+    #[test]
+    fn test_plan_is_exact() {
+        use crate::stego::whitespace::WhitespaceCodec;
+        use crate::stego::Placement;
+
+        let alice = Agent::new("alice");
+        let bob = Agent::new("bob");
+        let secret = "see you at the usual place";
+        let cover = "Short cover.";
+
+        let plan = Waterscape::plan(secret.len());
+        let encoded = Waterscape::encode(&alice, &bob.public_identity(), cover, secret).unwrap();
+        assert_eq!(encoded.chars().count() - cover.chars().count(), plan.requirement.encoded_len);
+        assert_eq!(
+            (plan.requirement.min_cover, plan.requirement.unit),
+            (plan.requirement.encoded_len.div_ceil(crate::stego::DEFAULT_MAX_PER_CHAR), "words")
+        );
+
+        let group = WaterscapeGroup::new("council", &alice, vec![alice.public_identity(), bob.public_identity()])
+            .with_codec(WhitespaceCodec);
+        let plan = group.plan(secret.len());
+        assert_eq!(plan.message_len, message_len(secret.len(), Recipient::Direct) + 2 + "council".len());
+        let encoded = group.encode(&alice, cover, secret).unwrap();
+        assert_eq!(encoded.len() - cover.len(), plan.requirement.encoded_len);
+        assert_eq!((plan.requirement.min_cover, plan.requirement.unit), (1, "lines"));
+
+        let bound = WaterscapeChannel::establish(&alice, &bob.public_identity()).unwrap().with_cover_binding(true);
+        let plan = bound.plan(secret.len());
+        assert_eq!(plan.message_len, message_len(secret.len(), Recipient::Direct) + COVER_HASH_SIZE);
+        let encoded = bound.encode(&alice, cover, secret).unwrap();
+        assert_eq!(encoded.chars().count() - cover.chars().count(), plan.requirement.encoded_len);
+
+        // Strict placement refuses covers shorter than planned
+        let strict = ZeroWidthCodec::default().with_placement(Placement::strict());
+        let plan = super::plan(&strict, secret.len(), Recipient::Direct);
+        let short_cover = "x".repeat(plan.requirement.min_cover - 1);
+        let long_cover = "x".repeat(plan.requirement.min_cover);
+        assert!(matches!(
+            Waterscape::encode_with_codec(strict, &alice, &bob.public_identity(), &short_cover, secret),
+            Err(WaterscapeError::CoverTextTooShort { .. })
+        ));
+        assert!(Waterscape::encode_with_codec(strict, &alice, &bob.public_identity(), &long_cover, secret).is_ok());
+    }
+
+    #[test]
+    fn test_cover_binding() {
+        let alice = Agent::new("alice");
+        let bob = Agent::new("bob");
+        let cover = "Quarterly numbers look great, congrats to the whole team!";
+        let secret = "sell everything on friday";
+
+        let encoded = Waterscape::encode_bound(&alice, &bob.public_identity(), cover, secret).unwrap();
+        let decoded = Waterscape::decode_verified(&bob, &alice.public_identity(), &encoded).unwrap();
+        assert_eq!(decoded.content, secret);
+        assert_eq!(decoded.cover, CoverIntegrity::Intact);
+
+        // Reflowed whitespace is not an edit
+        let reflowed = encoded.replace(' ', "  ").replace("team!", "team!\n");
+        let decoded = Waterscape::decode_verified(&bob, &alice.public_identity(), &reflowed).unwrap();
+        assert_eq!(decoded.cover, CoverIntegrity::Intact);
+
+        // Editing the visible words is detected
+        let edited = encoded.replace("great", "awful");
+        let decoded = Waterscape::decode_verified(&bob, &alice.public_identity(), &edited).unwrap();
+        assert_eq!(decoded.cover, CoverIntegrity::Mismatch);
+
+        // So is moving the payload onto a different text
+        let payload: String = encoded.chars().filter(|&c| crate::detector::is_invisible(c)).collect();
+        let transplanted = format!("Buy{} now, prices only go up.", payload);
+        let decoded = Waterscape::decode_verified(&bob, &alice.public_identity(), &transplanted).unwrap();
+        assert_eq!(decoded.content, secret);
+        assert_eq!(decoded.cover, CoverIntegrity::Mismatch);
+
+        // Unbound messages say so
+        let encoded = Waterscape::encode(&alice, &bob.public_identity(), cover, secret).unwrap();
+        let decoded = Waterscape::decode_verified(&bob, &alice.public_identity(), &encoded).unwrap();
+        assert_eq!(decoded.cover, CoverIntegrity::Unbound);
+
+        let group = WaterscapeGroup::new("council", &alice, vec![alice.public_identity()])
+            .with_codec(crate::stego::whitespace::WhitespaceCodec)
+            .with_cover_binding(true);
+        let encoded = group.encode(&alice, cover, "ok").unwrap();
+        assert_eq!(group.decode_verified(&encoded).unwrap().cover, CoverIntegrity::Intact);
+    }
+
+    #[test]
+    fn test_group_key_envelopes() {
+        let alice = Agent::new("alice");
+        let bob = Agent::new("bob");
+        let charlie = Agent::new("charlie");
+        let eve = Agent::new("eve");
+
+        // The creator is added to the members automatically
+        let group = WaterscapeGroup::new("council", &alice, vec![bob.public_identity(), charlie.public_identity()]);
+        assert_eq!(group.members().len(), 3);
+
+        let envelopes = group.envelopes(&alice).unwrap();
+        assert_eq!(envelopes.len(), 2);
+        let for_bob = GroupKeyEnvelope::from_bytes(&envelopes[0].to_bytes().unwrap()).unwrap();
+        let bob_group = WaterscapeGroup::from_envelope(&bob, &for_bob).unwrap();
+        let charlie_group = WaterscapeGroup::from_envelope(&charlie, &envelopes[1]).unwrap();
+        assert_eq!(bob_group.name(), "council");
+        assert_eq!(bob_group.members().len(), 3);
+
+        // Every member can send to every other member
+        let cover = "Minutes of the last meeting are attached below.";
+        let encoded = bob_group.encode(&bob, cover, "from bob").unwrap();
+        assert_eq!(group.decode(&encoded).unwrap(), "from bob");
+        assert_eq!(charlie_group.decode(&encoded).unwrap(), "from bob");
+
+        // Envelopes only open for their recipient
+        assert!(WaterscapeGroup::from_envelope(&eve, &for_bob).is_err());
+        assert!(WaterscapeGroup::from_envelope(&charlie, &for_bob).is_err());
+
+        // The member list and roles are signed
+        let mut tampered = for_bob.clone();
+        tampered.members.push(GroupMember::new(eve.public_identity(), GroupRole::Admin));
+        assert!(matches!(
+            WaterscapeGroup::from_envelope(&bob, &tampered),
+            Err(WaterscapeError::InvalidSignature)
+        ));
+        let mut tampered = for_bob.clone();
+        tampered.members[1].role = GroupRole::Admin;
+        assert!(matches!(
+            WaterscapeGroup::from_envelope(&bob, &tampered),
+            Err(WaterscapeError::InvalidSignature)
+        ));
+
+        // Only admins issue envelopes, and only to members
+        assert!(matches!(group.envelope_for(&eve, &bob.public_identity()), Err(WaterscapeError::Unauthorized)));
+        assert!(matches!(bob_group.envelope_for(&bob, &charlie.public_identity()), Err(WaterscapeError::Unauthorized)));
+        assert!(matches!(group.envelope_for(&alice, &eve.public_identity()), Err(WaterscapeError::Unauthorized)));
+    }
+
+    #[test]
+    fn test_group_epochs() {
+        let alice = Agent::new("alice");
+        let bob = Agent::new("bob");
+        let charlie = Agent::new("charlie");
+        let dave = Agent::new("dave");
+        let cover = "Weekly status: everything on track, no blockers reported.";
+
+        let mut group = WaterscapeGroup::new("council", &alice, vec![bob.public_identity(), charlie.public_identity()]);
+        let envelopes = group.envelopes(&alice).unwrap();
+        let mut bob_group = WaterscapeGroup::from_envelope(&bob, &envelopes[0]).unwrap();
+        let mut charlie_group = WaterscapeGroup::from_envelope(&charlie, &envelopes[1]).unwrap();
+        let before = group.encode(&alice, cover, "epoch zero").unwrap();
+
+        // Removing Charlie re-keys for Alice and Bob only
+        let envelopes = group.remove_member(&alice, &charlie.public_identity().signing_key).unwrap();
+        assert_eq!((group.epoch(), envelopes.len()), (1, 1));
+        bob_group.apply_envelope(&bob, &envelopes[0]).unwrap();
+        assert_eq!(bob_group.epoch(), 1);
+        assert_eq!(bob_group.members().len(), 2);
+        assert!(charlie_group.apply_envelope(&charlie, &envelopes[0]).is_err());
+
+        let after = group.encode(&alice, cover, "epoch one").unwrap();
+        assert_eq!(bob_group.decode(&after).unwrap(), "epoch one");
+        assert!(matches!(charlie_group.decode(&after), Err(WaterscapeError::UnknownEpoch { epoch: 1 })));
+
+        // History stays readable for those who were members then
+        assert_eq!(bob_group.decode(&before).unwrap(), "epoch zero");
+        assert_eq!(charlie_group.decode(&before).unwrap(), "epoch zero");
+
+        // Members need admin rights to re-key; promoting Bob is itself a re-key
+        assert!(matches!(
+            bob_group.add_member(&bob, dave.public_identity(), GroupRole::Member),
+            Err(WaterscapeError::Unauthorized)
+        ));
+        let envelopes = group.set_role(&alice, &bob.public_identity().signing_key, GroupRole::Admin).unwrap();
+        bob_group.apply_envelope(&bob, &envelopes[0]).unwrap();
+        assert_eq!(bob_group.role(&bob.public_identity().signing_key), Some(GroupRole::Admin));
+
+        // A new member only reads from the epoch it joined in
+        let envelopes = bob_group.add_member(&bob, dave.public_identity(), GroupRole::Member).unwrap();
+        assert_eq!(bob_group.epoch(), 3);
+        let dave_envelope = envelopes.iter().find(|e| e.recipient_key == dave.public_identity().exchange_key).unwrap();
+        let dave_group = WaterscapeGroup::from_envelope(&dave, dave_envelope).unwrap();
+        for envelope in envelopes.iter().filter(|e| e.recipient_key == alice.public_identity().exchange_key) {
+            group.apply_envelope(&alice, envelope).unwrap();
+        }
+        let latest = group.encode(&alice, cover, "epoch three").unwrap();
+        assert_eq!(dave_group.decode(&latest).unwrap(), "epoch three");
+        assert!(dave_group.decode(&after).is_err());
+
+        // Removed members cannot re-key the group
+        let rogue = WaterscapeGroup::new("council", &charlie, vec![alice.public_identity()]);
+        let forged = rogue.envelope_for(&charlie, &alice.public_identity()).unwrap();
+        assert!(matches!(group.apply_envelope(&alice, &forged), Err(WaterscapeError::AuthenticationFailed)));
+        assert!(matches!(
+            group.add_member(&alice, bob.public_identity(), GroupRole::Member),
+            Err(WaterscapeError::Membership(_))
+        ));
+    }
+
+    #[test]
+    fn test_concurrent_rekey_rejected() {
+        let alice = Agent::new("alice");
+        let bob = Agent::new("bob");
+        let carol = Agent::new("carol");
+        let dave = Agent::new("dave");
+        let cover = "Nothing to see here.";
+
+        let members = vec![alice.public_identity(), bob.public_identity(), carol.public_identity()];
+        let mut alice_group = WaterscapeGroup::new("council", &alice, members);
+        let bob_key = alice_group.envelope_for(&alice, &bob.public_identity()).unwrap();
+        let carol_key = alice_group.envelope_for(&alice, &carol.public_identity()).unwrap();
+        let mut bob_group = WaterscapeGroup::from_envelope(&bob, &bob_key).unwrap();
+        let mut carol_group = WaterscapeGroup::from_envelope(&carol, &carol_key).unwrap();
+        let envelopes = alice_group.set_role(&alice, &bob.public_identity().signing_key, GroupRole::Admin).unwrap();
+        for envelope in &envelopes {
+            if envelope.recipient_key == bob.public_identity().exchange_key {
+                bob_group.apply_envelope(&bob, envelope).unwrap();
+            } else {
+                carol_group.apply_envelope(&carol, envelope).unwrap();
+            }
+        }
+        assert_eq!((bob_group.epoch(), carol_group.epoch()), (1, 1));
+
+        // Both admins re-key from epoch 1 at the same time
+        let from_alice = alice_group.add_member(&alice, dave.public_identity(), GroupRole::Member).unwrap();
+        let from_bob = bob_group.remove_member(&bob, &carol.public_identity().signing_key).unwrap();
+        assert_eq!((alice_group.epoch(), bob_group.epoch()), (2, 2));
+
+        let for_carol = from_alice.iter().find(|e| e.recipient_key == carol.public_identity().exchange_key).unwrap();
+        carol_group.apply_envelope(&carol, for_carol).unwrap();
+        carol_group.apply_envelope(&carol, for_carol).unwrap();
+        let for_alice = from_bob.iter().find(|e| e.recipient_key == alice.public_identity().exchange_key).unwrap();
+        match alice_group.apply_envelope(&alice, for_alice) {
+            Err(WaterscapeError::Membership(reason)) => assert_eq!(reason, "Conflicting key for epoch 2"),
+            other => panic!("Expected a conflicting epoch, got {:?}", other),
+        }
+
+        // The epoch that was kept still works
+        let message = alice_group.encode(&alice, cover, "epoch two").unwrap();
+        assert_eq!(carol_group.decode(&message).unwrap(), "epoch two");
+    }
+
+    #[test]
+    fn test_group_membership_enforced() {
+        let alice = Agent::new("alice");
+        let bob = Agent::new("bob");
+        let reader = Agent::new("reader");
+        let mallory = Agent::new("mallory");
+        let cover = "Release checklist: tests green, changelog updated, tag pending.";
+
+        let mut group = WaterscapeGroup::new("council", &alice, vec![bob.public_identity()]);
+        let envelopes = group.add_member(&alice, reader.public_identity(), GroupRole::ReadOnly).unwrap();
+        let bob_group = WaterscapeGroup::from_envelope(&bob, &envelopes[0]).unwrap();
+        let reader_group = WaterscapeGroup::from_envelope(&reader, &envelopes[1]).unwrap();
+        assert_eq!(reader_group.role(&reader.public_identity().signing_key), Some(GroupRole::ReadOnly));
+
+        // Decoding returns the verified sender
+        let encoded = bob_group.encode(&bob, cover, "from bob").unwrap();
+        let decoded = reader_group.decode_verified(&encoded).unwrap();
+        assert_eq!(decoded.content, "from bob");
+        assert_eq!(decoded.sender, bob.public_identity());
+
+        // Read-only members cannot post, even by signing a message themselves
+        assert!(matches!(reader_group.encode(&reader, cover, "hi"), Err(WaterscapeError::Unauthorized)));
+        let mut forger = WaterscapeGroup::from_envelope(&reader, &envelopes[1]).unwrap();
+        let current = forger.epochs.get_mut(&forger.epoch).unwrap();
+        current.members[2].role = GroupRole::Member;
+        let forged = forger.encode(&reader, cover, "as reader").unwrap();
+        assert!(matches!(bob_group.decode(&forged), Err(WaterscapeError::SenderNotPermitted { .. })));
+
+        // Holding the group key does not make an outsider a member
+        let current = forger.epochs.get_mut(&forger.epoch).unwrap();
+        current.members.push(GroupMember::new(mallory.public_identity(), GroupRole::Member));
+        let forged = forger.encode(&mallory, cover, "as mallory").unwrap();
+        assert!(matches!(group.decode_verified(&forged), Err(WaterscapeError::SenderNotPermitted { .. })));
+
+        // The last admin cannot be demoted
+        assert!(matches!(
+            group.set_role(&alice, &alice.public_identity().signing_key, GroupRole::Member),
+            Err(WaterscapeError::Membership(_))
+        ));
+    }
+
     #[test]
     fn test_group_communication() {
         let alice = Agent::new("alice");
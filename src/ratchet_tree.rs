@@ -0,0 +1,1096 @@
+//! Tree-based group key management
+//!
+//! A [`TreeGroup`] arranges its members at the leaves of a binary ratchet tree,
+//! after MLS/TreeKEM. Every node holds an X25519 key pair; a member knows the
+//! private keys of the nodes on the path from its leaf to the root, and the
+//! root secret determines the group key of each epoch.
+//!
+//! A change is made by one member in a [`Commit`]: it blanks removed leaves,
+//! fills new ones, and replaces every key on the committer's path with fresh
+//! ones derived from a chain of path secrets:
+//!
+//! ```text
+//! path_secret[0]   = HKDF(leaf_secret,      "waterscape-v2-tree-path")
+//! path_secret[n+1] = HKDF(path_secret[n],   "waterscape-v2-tree-path")
+//! node_key[n]      = HKDF(path_secret[n],   "waterscape-v2-tree-node")
+//! commit_secret    = HKDF(path_secret[root], "waterscape-v2-tree-path")
+//! ```
+//!
+//! Each path secret is sealed only to the nodes that cover the other half of
+//! its subtree, so a commit carries `O(log n)` ciphertexts once the tree is
+//! full, instead of one envelope per member. New members receive the tree and
+//! their secrets in a [`Welcome`]. Commits and welcomes serialize to bytes and
+//! can be hidden in cover text like any other message.
+//!
+//! Application messages use the group key of the current epoch through the
+//! same wire format as [`WaterscapeGroup`], including its sender checks.
+//! Initial leaf keys are the members' identity exchange keys.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use x25519_dalek::PublicKey as X25519PublicKey;
+use zeroize::{Zeroize, Zeroizing};
+
+use crate::agent::{Agent, PublicIdentity};
+use crate::crypto::{self, EphemeralKeyPair, KeyExchangePair, KEY_SIZE, NONCE_SIZE};
+use crate::error::WaterscapeError;
+use crate::protocol::{push_str, CoverPlan, DecodedMessage, GroupMember, GroupRole, WaterscapeGroup};
+use crate::replay::ReplayGuard;
+use crate::stego::StegoCodec;
+use crate::Result;
+
+/// Version of the commit and welcome formats
+pub const TREE_VERSION: u8 = 1;
+
+const CONTEXT_PATH: &[u8] = b"waterscape-v2-tree-path";
+const CONTEXT_NODE: &[u8] = b"waterscape-v2-tree-node";
+const CONTEXT_SEAL: &[u8] = b"waterscape-v2-tree-seal";
+const CONTEXT_JOINER: &[u8] = b"waterscape-v2-tree-joiner";
+const CONTEXT_EPOCH: &[u8] = b"waterscape-v2-tree-epoch";
+const CONTEXT_GROUP_KEY: &[u8] = b"waterscape-v2-tree-group-key";
+const CONTEXT_INIT: &[u8] = b"waterscape-v2-tree-init";
+const CONTEXT_TREE_HASH: &[u8] = b"waterscape-v2-tree-hash";
+const CONTEXT_COMMIT_SIGN: &[u8] = b"waterscape-v2-tree-commit-sign";
+const CONTEXT_WELCOME_SIGN: &[u8] = b"waterscape-v2-tree-welcome-sign";
+
+// Tree math over node indices: leaves sit at even indices, parents at odd
+// ones, and the tree always has a power-of-two number of leaves.
+
+fn level(node: usize) -> u32 {
+    node.trailing_ones()
+}
+
+fn leaf_node(leaf: u32) -> usize {
+    2 * leaf as usize
+}
+
+fn root(leaf_count: usize) -> usize {
+    leaf_count - 1
+}
+
+fn parent(node: usize) -> usize {
+    let k = level(node);
+    if (node >> (k + 1)) & 1 == 0 {
+        node + (1 << k)
+    } else {
+        node - (1 << k)
+    }
+}
+
+fn sibling(node: usize) -> usize {
+    2 * parent(node) - node
+}
+
+fn children(node: usize) -> (usize, usize) {
+    let half = 1 << (level(node) - 1);
+    (node - half, node + half)
+}
+
+/// Whether `node` lies in the subtree rooted at `ancestor`
+fn in_subtree(ancestor: usize, node: usize) -> bool {
+    let span = (1 << level(ancestor)) - 1;
+    node + span >= ancestor && node <= ancestor + span
+}
+
+/// Parents from just above `node` up to the root
+fn direct_path(node: usize, leaf_count: usize) -> Vec<usize> {
+    let root = root(leaf_count);
+    let mut path = Vec::new();
+    let mut current = node;
+    while current != root {
+        current = parent(current);
+        path.push(current);
+    }
+    path
+}
+
+/// Key pair of a node, derived from its path secret
+fn node_keypair(secret: &[u8; KEY_SIZE]) -> KeyExchangePair {
+    let mut seed = crypto::derive_from_seed(secret, CONTEXT_NODE);
+    let pair = KeyExchangePair::from_bytes(&seed);
+    seed.zeroize();
+    pair
+}
+
+fn next_path_secret(secret: &[u8; KEY_SIZE]) -> [u8; KEY_SIZE] {
+    crypto::derive_from_seed(secret, CONTEXT_PATH)
+}
+
+/// A member's leaf
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LeafNode {
+    pub member: GroupMember,
+    #[serde(with = "hex::serde")]
+    pub encryption_key: [u8; 32],
+}
+
+/// An inner node of the tree
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ParentNode {
+    #[serde(with = "hex::serde")]
+    pub public_key: [u8; 32],
+    /// Leaves added below this node since its key was set; they do not know it
+    #[serde(default)]
+    pub unmerged_leaves: Vec<u32>,
+}
+
+/// Public state of a ratchet tree
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RatchetTree {
+    leaves: Vec<Option<LeafNode>>,
+    parents: Vec<Option<ParentNode>>,
+}
+
+impl RatchetTree {
+    fn new(creator: LeafNode) -> Self {
+        Self {
+            leaves: vec![Some(creator)],
+            parents: Vec::new(),
+        }
+    }
+
+    /// Check the shape of a tree received from another member
+    ///
+    /// Every unmerged leaf must be an occupied leaf below the node listing it.
+    fn validate(&self) -> Result<()> {
+        let malformed = |reason: &str| WaterscapeError::Decoding(format!("Malformed ratchet tree: {}", reason));
+        if !self.leaves.len().is_power_of_two() || self.parents.len() + 1 != self.leaves.len() {
+            return Err(malformed("wrong node count"));
+        }
+        for (i, parent) in self.parents.iter().enumerate() {
+            let Some(parent) = parent else { continue };
+            let node = 2 * i + 1;
+            for (j, &leaf) in parent.unmerged_leaves.iter().enumerate() {
+                if self.leaf(leaf).is_none() || !in_subtree(node, leaf_node(leaf)) {
+                    return Err(malformed("unmerged leaf outside its subtree"));
+                }
+                if parent.unmerged_leaves[..j].contains(&leaf) {
+                    return Err(malformed("duplicate unmerged leaf"));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn leaf_count(&self) -> usize {
+        self.leaves.len()
+    }
+
+    pub fn leaf(&self, leaf: u32) -> Option<&LeafNode> {
+        self.leaves.get(leaf as usize).and_then(Option::as_ref)
+    }
+
+    fn find_leaf(&self, signing_key: &[u8; 32]) -> Option<u32> {
+        self.leaves
+            .iter()
+            .position(|l| l.as_ref().is_some_and(|l| &l.member.identity.signing_key == signing_key))
+            .map(|i| i as u32)
+    }
+
+    /// Members in leaf order
+    pub fn members(&self) -> Vec<GroupMember> {
+        self.leaves.iter().flatten().map(|l| l.member.clone()).collect()
+    }
+
+    fn parent_node(&self, node: usize) -> Option<&ParentNode> {
+        self.parents[node / 2].as_ref()
+    }
+
+    fn is_blank(&self, node: usize) -> bool {
+        if node.is_multiple_of(2) {
+            self.leaves[node / 2].is_none()
+        } else {
+            self.parents[node / 2].is_none()
+        }
+    }
+
+    fn public_key(&self, node: usize) -> Option<[u8; 32]> {
+        if node.is_multiple_of(2) {
+            self.leaves[node / 2].as_ref().map(|l| l.encryption_key)
+        } else {
+            self.parent_node(node).map(|p| p.public_key)
+        }
+    }
+
+    /// Non-blank nodes that together cover every member below `node`
+    fn resolution(&self, node: usize) -> Vec<usize> {
+        if node.is_multiple_of(2) {
+            return if self.is_blank(node) { Vec::new() } else { vec![node] };
+        }
+        match self.parent_node(node) {
+            Some(p) => std::iter::once(node).chain(p.unmerged_leaves.iter().map(|&l| leaf_node(l))).collect(),
+            None => {
+                let (left, right) = children(node);
+                let mut resolution = self.resolution(left);
+                resolution.extend(self.resolution(right));
+                resolution
+            }
+        }
+    }
+
+    /// Double the number of leaves; existing node indices do not change
+    fn extend(&mut self) {
+        let leaf_count = self.leaves.len() * 2;
+        self.leaves.resize(leaf_count, None);
+        self.parents.resize(leaf_count - 1, None);
+    }
+
+    /// Apply a commit's removals and additions, returning the added leaves
+    fn apply_changes(&mut self, committer: u32, removes: &[u32], adds: &[GroupMember]) -> Result<Vec<u32>> {
+        for &leaf in removes {
+            if leaf == committer {
+                return Err(WaterscapeError::Membership("A member cannot remove itself".into()));
+            }
+            if self.leaf(leaf).is_none() {
+                return Err(WaterscapeError::Membership(format!("Leaf {} is not a member", leaf)));
+            }
+            self.leaves[leaf as usize] = None;
+            for node in direct_path(leaf_node(leaf), self.leaf_count()) {
+                self.parents[node / 2] = None;
+            }
+        }
+
+        let mut added = Vec::with_capacity(adds.len());
+        for member in adds {
+            if self.find_leaf(&member.identity.signing_key).is_some() {
+                return Err(WaterscapeError::Membership(format!("'{}' is already a member", member.identity.name)));
+            }
+            let leaf = match self.leaves.iter().position(Option::is_none) {
+                Some(leaf) => leaf,
+                None => {
+                    let leaf = self.leaves.len();
+                    self.extend();
+                    leaf
+                }
+            } as u32;
+            self.leaves[leaf as usize] = Some(LeafNode {
+                member: member.clone(),
+                encryption_key: member.identity.exchange_key,
+            });
+            for node in direct_path(leaf_node(leaf), self.leaf_count()) {
+                if let Some(p) = self.parents[node / 2].as_mut() {
+                    p.unmerged_leaves.push(leaf);
+                }
+            }
+            added.push(leaf);
+        }
+        Ok(added)
+    }
+
+    /// Install the committer's new leaf key and path keys
+    fn apply_path(&mut self, committer: u32, path: &UpdatePath) -> Result<()> {
+        let nodes = direct_path(leaf_node(committer), self.leaf_count());
+        if nodes.len() != path.nodes.len() {
+            return Err(WaterscapeError::Decoding("Commit path does not match the tree".into()));
+        }
+        let leaf = self.leaves[committer as usize]
+            .as_mut()
+            .ok_or_else(|| WaterscapeError::Membership("Committer is not a member".into()))?;
+        leaf.encryption_key = path.leaf_key;
+        for (node, update) in nodes.iter().zip(&path.nodes) {
+            self.parents[node / 2] = Some(ParentNode {
+                public_key: update.public_key,
+                unmerged_leaves: Vec::new(),
+            });
+        }
+        Ok(())
+    }
+
+    fn hash(&self) -> Result<[u8; 32]> {
+        let mut hasher = Sha256::new();
+        hasher.update(CONTEXT_TREE_HASH);
+        hasher.update(serde_json::to_vec(self)?);
+        Ok(hasher.finalize().into())
+    }
+}
+
+/// A secret encrypted to one node's public key
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SealedSecret {
+    #[serde(with = "hex::serde")]
+    pub ephemeral_key: [u8; 32],
+    #[serde(with = "hex::serde")]
+    pub ciphertext: Vec<u8>,
+}
+
+impl SealedSecret {
+    /// Encrypt to a node key; the key is only used once, so the nonce is fixed
+    fn seal(recipient_key: &[u8; 32], secret: &[u8; KEY_SIZE], context: &[u8]) -> Result<Self> {
+        let ephemeral = EphemeralKeyPair::generate();
+        let ephemeral_key = ephemeral.public_key_bytes();
+        let shared = ephemeral.diffie_hellman(&X25519PublicKey::from(*recipient_key));
+        let mut key = shared.derive_key(&Self::info(context, &ephemeral_key, recipient_key));
+        let ciphertext = crypto::encrypt(&key, &[0u8; NONCE_SIZE], secret);
+        key.zeroize();
+        Ok(Self {
+            ephemeral_key,
+            ciphertext: ciphertext?,
+        })
+    }
+
+    fn open(&self, recipient: &KeyExchangePair, context: &[u8]) -> Result<[u8; KEY_SIZE]> {
+        let shared = recipient.diffie_hellman(&X25519PublicKey::from(self.ephemeral_key));
+        let mut key = shared.derive_key(&Self::info(context, &self.ephemeral_key, &recipient.public_key_bytes()));
+        let secret = crypto::decrypt(&key, &[0u8; NONCE_SIZE], &self.ciphertext);
+        key.zeroize();
+        let mut secret = secret?;
+        let result = <[u8; KEY_SIZE]>::try_from(secret.as_slice())
+            .map_err(|_| WaterscapeError::Crypto("Invalid sealed secret length".into()));
+        secret.zeroize();
+        result
+    }
+
+    fn info(context: &[u8], ephemeral_key: &[u8; 32], recipient_key: &[u8; 32]) -> Vec<u8> {
+        let mut info = Vec::with_capacity(CONTEXT_SEAL.len() + context.len() + 64);
+        info.extend_from_slice(CONTEXT_SEAL);
+        info.extend_from_slice(context);
+        info.extend_from_slice(ephemeral_key);
+        info.extend_from_slice(recipient_key);
+        info
+    }
+}
+
+/// Group and epoch a sealed secret belongs to
+fn seal_context(group_name: &str, epoch: u32) -> Vec<u8> {
+    let mut context = Vec::new();
+    push_str(&mut context, group_name);
+    context.extend_from_slice(&epoch.to_be_bytes());
+    context
+}
+
+/// New public key of one node on the committer's path
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct UpdatePathNode {
+    #[serde(with = "hex::serde")]
+    pub public_key: [u8; 32],
+    /// The node's path secret, sealed to each node in the resolution of the
+    /// other child, in resolution order, leaving out leaves added by the commit
+    pub secrets: Vec<SealedSecret>,
+}
+
+/// The committer's new leaf key and path
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct UpdatePath {
+    #[serde(with = "hex::serde")]
+    pub leaf_key: [u8; 32],
+    pub nodes: Vec<UpdatePathNode>,
+}
+
+/// A change to a [`TreeGroup`], moving it to the next epoch
+///
+/// Removals are applied first, then additions fill the leftmost blank leaves,
+/// then the committer's path is replaced.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Commit {
+    pub version: u8,
+    pub group_name: String,
+    /// Epoch the commit applies to
+    pub epoch: u32,
+    /// Leaf of the committing member
+    pub committer: u32,
+    pub removes: Vec<u32>,
+    pub adds: Vec<GroupMember>,
+    pub path: UpdatePath,
+    #[serde(with = "hex::serde")]
+    pub signature: Vec<u8>,
+}
+
+impl Commit {
+    /// Serialize to JSON bytes
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        serde_json::to_vec(self).map_err(|e| WaterscapeError::Serialization(e.to_string()))
+    }
+
+    /// Deserialize from JSON bytes
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let commit: Self = serde_json::from_slice(bytes)?;
+        check_version(commit.version)?;
+        Ok(commit)
+    }
+
+    /// Canonical bytes signed by the committer
+    fn transcript(&self) -> Result<Vec<u8>> {
+        let unsigned = Self {
+            signature: Vec::new(),
+            ..self.clone()
+        };
+        let mut transcript = CONTEXT_COMMIT_SIGN.to_vec();
+        transcript.extend_from_slice(&serde_json::to_vec(&unsigned)?);
+        Ok(transcript)
+    }
+}
+
+/// Secrets for one member added by a commit
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct WelcomeSecret {
+    /// Exchange key of the new member
+    #[serde(with = "hex::serde")]
+    pub recipient_key: [u8; 32],
+    pub joiner_secret: SealedSecret,
+    /// Path secret of the lowest node shared with the committer
+    pub path_secret: SealedSecret,
+}
+
+/// Everything members added by a commit need to join its epoch
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Welcome {
+    pub version: u8,
+    pub group_name: String,
+    pub epoch: u32,
+    pub committer: u32,
+    pub tree: RatchetTree,
+    pub secrets: Vec<WelcomeSecret>,
+    #[serde(with = "hex::serde")]
+    pub signature: Vec<u8>,
+}
+
+impl Welcome {
+    /// Serialize to JSON bytes
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        serde_json::to_vec(self).map_err(|e| WaterscapeError::Serialization(e.to_string()))
+    }
+
+    /// Deserialize from JSON bytes
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let welcome: Self = serde_json::from_slice(bytes)?;
+        check_version(welcome.version)?;
+        Ok(welcome)
+    }
+
+    /// Canonical bytes signed by the committer
+    fn transcript(&self) -> Result<Vec<u8>> {
+        let unsigned = Self {
+            signature: Vec::new(),
+            ..self.clone()
+        };
+        let mut transcript = CONTEXT_WELCOME_SIGN.to_vec();
+        transcript.extend_from_slice(&serde_json::to_vec(&unsigned)?);
+        Ok(transcript)
+    }
+}
+
+fn check_version(version: u8) -> Result<()> {
+    if version != TREE_VERSION {
+        return Err(WaterscapeError::VersionMismatch {
+            expected: TREE_VERSION,
+            got: version,
+        });
+    }
+    Ok(())
+}
+
+fn verify(signer: &PublicIdentity, transcript: &[u8], signature: &[u8]) -> Result<()> {
+    let signature: [u8; 64] = signature
+        .try_into()
+        .map_err(|_| WaterscapeError::Crypto("Invalid signature length".into()))?;
+    crypto::verify_signature(&signer.signing_key, transcript, &ed25519_dalek::Signature::from_bytes(&signature))
+}
+
+/// Joiner secret of an epoch: the commit secret mixed with the previous init secret
+fn joiner_secret(commit_secret: &[u8; KEY_SIZE], init_secret: &[u8; KEY_SIZE]) -> [u8; KEY_SIZE] {
+    let mut context = CONTEXT_JOINER.to_vec();
+    context.extend_from_slice(init_secret);
+    let joiner = crypto::derive_from_seed(commit_secret, &context);
+    context.zeroize();
+    joiner
+}
+
+/// Group key and next init secret of an epoch, bound to the tree it was agreed on
+fn epoch_keys(
+    group_name: &str,
+    epoch: u32,
+    tree: &RatchetTree,
+    joiner: &[u8; KEY_SIZE],
+) -> Result<([u8; KEY_SIZE], Zeroizing<[u8; KEY_SIZE]>)> {
+    let mut context = CONTEXT_EPOCH.to_vec();
+    context.extend_from_slice(&seal_context(group_name, epoch));
+    context.extend_from_slice(&tree.hash()?);
+    let mut epoch_secret = crypto::derive_from_seed(joiner, &context);
+    let group_key = crypto::derive_from_seed(&epoch_secret, CONTEXT_GROUP_KEY);
+    let init_secret = Zeroizing::new(crypto::derive_from_seed(&epoch_secret, CONTEXT_INIT));
+    epoch_secret.zeroize();
+    Ok((group_key, init_secret))
+}
+
+/// Private keys of path nodes, by node index
+type PathKeys = Vec<(usize, KeyExchangePair)>;
+
+/// Derive node keys from a path secret up to the root, checking them against
+/// the tree, and return them with the commit secret
+fn derive_path(
+    tree: &RatchetTree,
+    nodes: &[usize],
+    mut secret: [u8; KEY_SIZE],
+) -> Result<(PathKeys, [u8; KEY_SIZE])> {
+    let mut keys = Vec::with_capacity(nodes.len());
+    for &node in nodes {
+        let pair = node_keypair(&secret);
+        if tree.public_key(node) != Some(pair.public_key_bytes()) {
+            secret.zeroize();
+            return Err(WaterscapeError::AuthenticationFailed);
+        }
+        keys.push((node, pair));
+        let next = next_path_secret(&secret);
+        secret.zeroize();
+        secret = next;
+    }
+    Ok((keys, secret))
+}
+
+/// Group whose keys are agreed through a ratchet tree
+///
+/// Use it like a [`WaterscapeGroup`] for messages. Membership changes are
+/// [`Commit`]s that every other member passes to
+/// [`process_commit`](Self::process_commit); the committer applies its own
+/// commit immediately.
+pub struct TreeGroup {
+    name: String,
+    epoch: u32,
+    own_leaf: u32,
+    tree: RatchetTree,
+    /// Private keys of this member's leaf and of the nodes above it it knows
+    private_keys: HashMap<usize, KeyExchangePair>,
+    init_secret: Zeroizing<[u8; KEY_SIZE]>,
+    /// Keys and member lists of every epoch, for application messages
+    group: WaterscapeGroup,
+}
+
+impl TreeGroup {
+    /// Create a group with the creator as its only member and admin
+    pub fn new(name: &str, creator: &Agent) -> Self {
+        let member = GroupMember::new(creator.public_identity(), GroupRole::Admin);
+        let tree = RatchetTree::new(LeafNode {
+            encryption_key: member.identity.exchange_key,
+            member,
+        });
+        let leaf_key = KeyExchangePair::from_bytes(&creator.exchange_keypair().secret_key_bytes());
+
+        Self {
+            name: name.to_string(),
+            epoch: 0,
+            own_leaf: 0,
+            group: WaterscapeGroup::with_key(name, tree.members(), 0, crypto::generate_key()),
+            tree,
+            private_keys: HashMap::from([(leaf_node(0), leaf_key)]),
+            init_secret: Zeroizing::new(crypto::generate_key()),
+        }
+    }
+
+    /// Join a group from the welcome of the commit that added this agent
+    pub fn from_welcome(member: &Agent, welcome: &Welcome) -> Result<Self> {
+        welcome.tree.validate()?;
+        let identity = member.public_identity();
+        let own_leaf = welcome
+            .tree
+            .find_leaf(&identity.signing_key)
+            .ok_or(WaterscapeError::Unauthorized)?;
+        let committer = welcome
+            .tree
+            .leaf(welcome.committer)
+            .ok_or(WaterscapeError::AuthenticationFailed)?;
+        verify(&committer.member.identity, &welcome.transcript()?, &welcome.signature)?;
+        // Welcomes come from commits that add members, which only admins make
+        if committer.member.role != GroupRole::Admin {
+            return Err(WaterscapeError::Unauthorized);
+        }
+
+        let secrets = welcome
+            .secrets
+            .iter()
+            .find(|s| s.recipient_key == identity.exchange_key)
+            .ok_or_else(|| WaterscapeError::KeyExchange("Welcome has no secrets for this agent".into()))?;
+        let leaf_key = KeyExchangePair::from_bytes(&member.exchange_keypair().secret_key_bytes());
+        let context = seal_context(&welcome.group_name, welcome.epoch);
+        let mut joiner = secrets.joiner_secret.open(&leaf_key, &context)?;
+        let path_secret = secrets.path_secret.open(&leaf_key, &context)?;
+
+        // Path keys from the lowest node shared with the committer up to the root
+        let own_node = leaf_node(own_leaf);
+        let path = direct_path(leaf_node(welcome.committer), welcome.tree.leaf_count());
+        let start = path
+            .iter()
+            .position(|&node| in_subtree(node, own_node))
+            .ok_or_else(|| WaterscapeError::Decoding("Welcome path does not cover this member".into()))?;
+        let (keys, mut commit_secret) = derive_path(&welcome.tree, &path[start..], path_secret)?;
+        commit_secret.zeroize();
+
+        let (group_key, init_secret) = epoch_keys(&welcome.group_name, welcome.epoch, &welcome.tree, &joiner)?;
+        joiner.zeroize();
+
+        let mut private_keys: HashMap<usize, KeyExchangePair> = keys.into_iter().collect();
+        private_keys.insert(own_node, leaf_key);
+        Ok(Self {
+            name: welcome.group_name.clone(),
+            epoch: welcome.epoch,
+            own_leaf,
+            group: WaterscapeGroup::with_key(&welcome.group_name, welcome.tree.members(), welcome.epoch, group_key),
+            tree: welcome.tree.clone(),
+            private_keys,
+            init_secret,
+        })
+    }
+
+    /// Use a different steganographic carrier for messages and commits
+    pub fn with_codec(mut self, codec: impl StegoCodec + 'static) -> Self {
+        self.group = self.group.with_codec(codec);
+        self
+    }
+
+    /// Add a member with a role; returns the commit for current members and
+    /// the welcome for the new one
+    pub fn add_member(&mut self, committer: &Agent, member: PublicIdentity, role: GroupRole) -> Result<(Commit, Welcome)> {
+        let (commit, welcome) = self.commit(committer, &[], vec![GroupMember::new(member, role)])?;
+        Ok((commit, welcome.expect("a commit with additions has a welcome")))
+    }
+
+    /// Remove a member; it cannot read messages from the next epoch on
+    pub fn remove_member(&mut self, committer: &Agent, signing_key: &[u8; 32]) -> Result<Commit> {
+        let leaf = self
+            .tree
+            .find_leaf(signing_key)
+            .ok_or_else(|| WaterscapeError::Membership("Not a member of this group".into()))?;
+        Ok(self.commit(committer, &[leaf], Vec::new())?.0)
+    }
+
+    /// Refresh this member's leaf and path keys
+    pub fn update(&mut self, committer: &Agent) -> Result<Commit> {
+        Ok(self.commit(committer, &[], Vec::new())?.0)
+    }
+
+    /// Create a commit and apply it locally
+    fn commit(
+        &mut self,
+        committer: &Agent,
+        removes: &[u32],
+        adds: Vec<GroupMember>,
+    ) -> Result<(Commit, Option<Welcome>)> {
+        let own = self
+            .tree
+            .leaf(self.own_leaf)
+            .ok_or_else(|| WaterscapeError::Membership("This member was removed from the group".into()))?;
+        if own.member.identity.signing_key != committer.public_identity().signing_key {
+            return Err(WaterscapeError::KeyExchange("Agent does not match the group's local member".into()));
+        }
+        if (!removes.is_empty() || !adds.is_empty()) && own.member.role != GroupRole::Admin {
+            return Err(WaterscapeError::Unauthorized);
+        }
+        let epoch = self
+            .epoch
+            .checked_add(1)
+            .ok_or_else(|| WaterscapeError::Membership("Group epoch exhausted".into()))?;
+        let context = seal_context(&self.name, epoch);
+
+        let mut tree = self.tree.clone();
+        let added = tree.apply_changes(self.own_leaf, removes, &adds)?;
+        let added_nodes: Vec<usize> = added.iter().map(|&l| leaf_node(l)).collect();
+
+        // Fresh leaf key, then one path secret per node up to the root
+        let own_node = leaf_node(self.own_leaf);
+        let path = direct_path(own_node, tree.leaf_count());
+        let mut leaf_secret = crypto::generate_key();
+        let leaf_key = node_keypair(&leaf_secret);
+        let mut path_secrets = Vec::with_capacity(path.len());
+        let mut secret = next_path_secret(&leaf_secret);
+        leaf_secret.zeroize();
+        for _ in &path {
+            path_secrets.push(Zeroizing::new(secret));
+            let next = next_path_secret(&secret);
+            secret.zeroize();
+            secret = next;
+        }
+        let mut commit_secret = secret;
+
+        let mut nodes = Vec::with_capacity(path.len());
+        let mut path_keys = Vec::with_capacity(path.len());
+        let mut child = own_node;
+        for (&node, path_secret) in path.iter().zip(&path_secrets) {
+            let pair = node_keypair(path_secret);
+            let secrets = tree
+                .resolution(sibling(child))
+                .into_iter()
+                .filter(|n| !added_nodes.contains(n))
+                .map(|n| {
+                    let key = tree
+                        .public_key(n)
+                        .ok_or_else(|| WaterscapeError::Decoding("Malformed ratchet tree: blank node in resolution".into()))?;
+                    SealedSecret::seal(&key, path_secret, &context)
+                })
+                .collect::<Result<Vec<_>>>()?;
+            nodes.push(UpdatePathNode {
+                public_key: pair.public_key_bytes(),
+                secrets,
+            });
+            path_keys.push((node, pair));
+            child = node;
+        }
+
+        let update_path = UpdatePath {
+            leaf_key: leaf_key.public_key_bytes(),
+            nodes,
+        };
+        tree.apply_path(self.own_leaf, &update_path)?;
+
+        let mut commit = Commit {
+            version: TREE_VERSION,
+            group_name: self.name.clone(),
+            epoch: self.epoch,
+            committer: self.own_leaf,
+            removes: removes.to_vec(),
+            adds,
+            path: update_path,
+            signature: Vec::new(),
+        };
+        commit.signature = committer.signing_keypair().sign(&commit.transcript()?).to_bytes().to_vec();
+
+        let mut joiner = joiner_secret(&commit_secret, &self.init_secret);
+        commit_secret.zeroize();
+
+        let welcome = if added.is_empty() {
+            None
+        } else {
+            let mut secrets = Vec::with_capacity(added.len());
+            for &leaf in &added {
+                let member = tree.leaf(leaf).expect("added leaf");
+                let shared = path
+                    .iter()
+                    .position(|&node| in_subtree(node, leaf_node(leaf)))
+                    .expect("the root covers every leaf");
+                secrets.push(WelcomeSecret {
+                    recipient_key: member.encryption_key,
+                    joiner_secret: SealedSecret::seal(&member.encryption_key, &joiner, &context)?,
+                    path_secret: SealedSecret::seal(&member.encryption_key, &path_secrets[shared], &context)?,
+                });
+            }
+            let mut welcome = Welcome {
+                version: TREE_VERSION,
+                group_name: self.name.clone(),
+                epoch,
+                committer: self.own_leaf,
+                tree: tree.clone(),
+                secrets,
+                signature: Vec::new(),
+            };
+            welcome.signature = committer.signing_keypair().sign(&welcome.transcript()?).to_bytes().to_vec();
+            Some(welcome)
+        };
+
+        self.private_keys.clear();
+        self.private_keys.insert(own_node, leaf_key);
+        self.private_keys.extend(path_keys);
+        let result = self.enter_epoch(tree, epoch, &joiner);
+        joiner.zeroize();
+        result?;
+        Ok((commit, welcome))
+    }
+
+    /// Verify and apply another member's commit
+    pub fn process_commit(&mut self, commit: &Commit) -> Result<()> {
+        if commit.group_name != self.name {
+            return Err(WaterscapeError::KeyExchange("Commit is for another group".into()));
+        }
+        if commit.epoch != self.epoch {
+            return Err(WaterscapeError::Membership(format!(
+                "Commit is for epoch {}, the group is at epoch {}",
+                commit.epoch, self.epoch
+            )));
+        }
+        let committer = self
+            .tree
+            .leaf(commit.committer)
+            .ok_or(WaterscapeError::AuthenticationFailed)?;
+        verify(&committer.member.identity, &commit.transcript()?, &commit.signature)?;
+        if (!commit.removes.is_empty() || !commit.adds.is_empty()) && committer.member.role != GroupRole::Admin {
+            return Err(WaterscapeError::Unauthorized);
+        }
+        if commit.committer == self.own_leaf {
+            return Err(WaterscapeError::Membership("Commit was created by this member".into()));
+        }
+
+        let epoch = self
+            .epoch
+            .checked_add(1)
+            .ok_or_else(|| WaterscapeError::Membership("Group epoch exhausted".into()))?;
+        let mut tree = self.tree.clone();
+        let added = tree.apply_changes(commit.committer, &commit.removes, &commit.adds)?;
+        if tree.leaf(self.own_leaf).is_none() {
+            return Err(WaterscapeError::Membership("This member was removed from the group".into()));
+        }
+        let added_nodes: Vec<usize> = added.iter().map(|&l| leaf_node(l)).collect();
+
+        // The first path node above this member carries a secret sealed to a
+        // node whose key this member holds
+        let own_node = leaf_node(self.own_leaf);
+        let path = direct_path(leaf_node(commit.committer), tree.leaf_count());
+        if path.len() != commit.path.nodes.len() {
+            return Err(WaterscapeError::Decoding("Commit path does not match the tree".into()));
+        }
+        let start = path
+            .iter()
+            .position(|&node| in_subtree(node, own_node))
+            .ok_or_else(|| WaterscapeError::Decoding("Commit path does not cover this member".into()))?;
+        let copath = sibling(if start == 0 { leaf_node(commit.committer) } else { path[start - 1] });
+        let resolution: Vec<usize> = tree
+            .resolution(copath)
+            .into_iter()
+            .filter(|n| !added_nodes.contains(n))
+            .collect();
+        let (index, holder) = resolution
+            .iter()
+            .enumerate()
+            .find_map(|(i, n)| self.private_keys.get(n).map(|key| (i, key)))
+            .ok_or_else(|| WaterscapeError::KeyExchange("No key to open the commit path".into()))?;
+        let sealed = commit.path.nodes[start]
+            .secrets
+            .get(index)
+            .ok_or_else(|| WaterscapeError::Decoding("Commit path is missing a secret".into()))?;
+        let path_secret = sealed.open(holder, &seal_context(&self.name, epoch))?;
+
+        tree.apply_path(commit.committer, &commit.path)?;
+        let (keys, mut commit_secret) = derive_path(&tree, &path[start..], path_secret)?;
+
+        // Keys of blanked and replaced nodes are gone
+        self.private_keys.retain(|&node, _| !tree.is_blank(node) && !path.contains(&node));
+        self.private_keys.extend(keys);
+
+        let mut joiner = joiner_secret(&commit_secret, &self.init_secret);
+        commit_secret.zeroize();
+        let result = self.enter_epoch(tree, epoch, &joiner);
+        joiner.zeroize();
+        result
+    }
+
+    fn enter_epoch(&mut self, tree: RatchetTree, epoch: u32, joiner: &[u8; KEY_SIZE]) -> Result<()> {
+        let (group_key, init_secret) = epoch_keys(&self.name, epoch, &tree, joiner)?;
+        self.group.insert_epoch(epoch, group_key, tree.members())?;
+        self.init_secret = init_secret;
+        self.tree = tree;
+        self.epoch = epoch;
+        Ok(())
+    }
+
+    /// Hide a commit in cover text with this group's carrier
+    pub fn encode_commit(&self, cover_text: &str, commit: &Commit) -> Result<String> {
+        self.group.codec().hide(cover_text, &commit.to_bytes()?)
+    }
+
+    /// Extract a commit hidden with [`encode_commit`](Self::encode_commit)
+    pub fn decode_commit(&self, text: &str) -> Result<Commit> {
+        Commit::from_bytes(&self.group.codec().extract(text)?)
+    }
+
+    /// Plan the cover text needed for a secret of `secret_len` bytes
+    pub fn plan(&self, secret_len: usize) -> CoverPlan {
+        self.group.plan(secret_len)
+    }
+
+    /// Encode message for the group
+    pub fn encode(&self, sender: &Agent, cover_text: &str, secret: &str) -> Result<String> {
+        self.group.encode(sender, cover_text, secret)
+    }
+
+    /// Decode group message
+    pub fn decode(&self, text: &str) -> Result<String> {
+        self.group.decode(text)
+    }
+
+    /// Decode group message together with its sender, and check it against the
+    /// visible text it arrived in
+    pub fn decode_verified(&self, text: &str) -> Result<DecodedMessage> {
+        self.group.decode_verified(text)
+    }
+
+    /// Decode group message, rejecting stale or already seen messages
+    pub fn decode_with_guard(&self, text: &str, guard: &mut ReplayGuard) -> Result<String> {
+        self.group.decode_with_guard(text, guard)
+    }
+
+    /// Get group name
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Current epoch
+    pub fn epoch(&self) -> u32 {
+        self.epoch
+    }
+
+    /// Public tree of the current epoch
+    pub fn tree(&self) -> &RatchetTree {
+        &self.tree
+    }
+
+    /// List current members and their roles
+    pub fn members(&self) -> &[GroupMember] {
+        self.group.members()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const COVER: &str = "Quarterly planning notes: hiring is on track and the roadmap review is next week.";
+
+    /// A group of `size` agents, each holding its own view
+    fn build(size: usize) -> (Vec<Agent>, Vec<TreeGroup>) {
+        let agents: Vec<Agent> = (0..size).map(|i| Agent::new(&format!("agent-{}", i))).collect();
+        let mut groups = vec![TreeGroup::new("hive", &agents[0])];
+        for agent in &agents[1..] {
+            let (commit, welcome) = groups[0].add_member(&agents[0], agent.public_identity(), GroupRole::Member).unwrap();
+            for group in &mut groups[1..] {
+                group.process_commit(&commit).unwrap();
+            }
+            let welcome = Welcome::from_bytes(&welcome.to_bytes().unwrap()).unwrap();
+            groups.push(TreeGroup::from_welcome(agent, &welcome).unwrap());
+        }
+        (agents, groups)
+    }
+
+    #[test]
+    fn test_tree_math() {
+        assert_eq!(direct_path(0, 8), vec![1, 3, 7]);
+        assert_eq!(direct_path(10, 8), vec![9, 11, 7]);
+        assert_eq!((sibling(1), sibling(3), sibling(12)), (5, 11, 14));
+        assert_eq!(children(11), (9, 13));
+        assert!(in_subtree(11, 8) && in_subtree(11, 14) && !in_subtree(11, 6));
+        assert!(direct_path(0, 1).is_empty());
+    }
+
+    #[test]
+    fn test_tree_group_membership() {
+        let (agents, mut groups) = build(5);
+        assert!(groups.iter().all(|g| g.epoch() == 4 && g.members().len() == 5));
+        assert_eq!(groups[0].tree().leaf_count(), 8);
+
+        // Every member reads every other member's messages
+        let encoded = groups[3].encode(&agents[3], COVER, "from three").unwrap();
+        for group in &groups {
+            let decoded = group.decode_verified(&encoded).unwrap();
+            assert_eq!((decoded.content.as_str(), decoded.sender.name.as_str()), ("from three", "agent-3"));
+        }
+
+        // A removed member misses the commit and every later epoch
+        let removed = agents[2].public_identity().signing_key;
+        let commit = groups[0].remove_member(&agents[0], &removed).unwrap();
+        for (i, group) in groups.iter_mut().enumerate().skip(1) {
+            let result = group.process_commit(&commit);
+            assert_eq!(result.is_ok(), i != 2);
+        }
+        let encoded = groups[1].encode(&agents[1], COVER, "without two").unwrap();
+        assert_eq!(groups[4].decode(&encoded).unwrap(), "without two");
+        assert!(matches!(groups[2].decode(&encoded), Err(WaterscapeError::UnknownEpoch { epoch: 5 })));
+
+        // Only admins change membership; anyone may refresh its own keys
+        let outsider = Agent::new("outsider");
+        assert!(matches!(
+            groups[1].add_member(&agents[1], outsider.public_identity(), GroupRole::Member),
+            Err(WaterscapeError::Unauthorized)
+        ));
+        let commit = groups[4].update(&agents[4]).unwrap();
+        for i in [0, 1, 3] {
+            groups[i].process_commit(&commit).unwrap();
+        }
+        let encoded = groups[0].encode(&agents[0], COVER, "after update").unwrap();
+        assert_eq!(groups[4].decode(&encoded).unwrap(), "after update");
+
+        // The freed leaf is reused by the next member
+        let (commit, welcome) = groups[0].add_member(&agents[0], outsider.public_identity(), GroupRole::ReadOnly).unwrap();
+        for i in [1, 3, 4] {
+            groups[i].process_commit(&commit).unwrap();
+        }
+        let outsider_group = TreeGroup::from_welcome(&outsider, &welcome).unwrap();
+        assert_eq!(outsider_group.tree().find_leaf(&outsider.public_identity().signing_key), Some(2));
+        let encoded = groups[1].encode(&agents[1], COVER, "welcome").unwrap();
+        assert_eq!(outsider_group.decode(&encoded).unwrap(), "welcome");
+        assert!(matches!(outsider_group.encode(&outsider, COVER, "hi"), Err(WaterscapeError::Unauthorized)));
+    }
+
+    #[test]
+    fn test_commit_cost_is_logarithmic() {
+        let (agents, mut groups) = build(8);
+
+        // Once every member has refreshed its path the tree is full
+        for i in 1..8 {
+            let commit = groups[i].update(&agents[i]).unwrap();
+            for (j, group) in groups.iter_mut().enumerate() {
+                if j != i {
+                    group.process_commit(&commit).unwrap();
+                }
+            }
+        }
+        let commit = groups[5].update(&agents[5]).unwrap();
+        assert_eq!(commit.path.nodes.len(), 3);
+        assert_eq!(commit.path.nodes.iter().map(|n| n.secrets.len()).sum::<usize>(), 3);
+    }
+
+    #[test]
+    fn test_malformed_welcome_rejected() {
+        let (agents, mut groups) = build(3);
+        let dave = Agent::new("dave");
+        let (_, welcome) = groups[0].add_member(&agents[0], dave.public_identity(), GroupRole::Member).unwrap();
+
+        // Correctly signed trees with bad unmerged leaves are refused, not trusted
+        for unmerged in [vec![u32::MAX], vec![1, 1], vec![2]] {
+            let mut forged = welcome.clone();
+            // Node 1 covers leaves 0 and 1
+            forged.tree.parents[0].as_mut().unwrap().unmerged_leaves = unmerged;
+            forged.signature = agents[0].signing_keypair().sign(&forged.transcript().unwrap()).to_bytes().to_vec();
+            assert!(matches!(TreeGroup::from_welcome(&dave, &forged), Err(WaterscapeError::Decoding(_))));
+        }
+
+        // A member signing a welcome it was not allowed to issue
+        let mut forged = welcome.clone();
+        forged.committer = 1;
+        forged.signature = agents[1].signing_keypair().sign(&forged.transcript().unwrap()).to_bytes().to_vec();
+        assert!(matches!(TreeGroup::from_welcome(&dave, &forged), Err(WaterscapeError::Unauthorized)));
+
+        let mut forged = welcome.clone();
+        forged.tree.parents.pop();
+        forged.signature = agents[0].signing_keypair().sign(&forged.transcript().unwrap()).to_bytes().to_vec();
+        assert!(matches!(TreeGroup::from_welcome(&dave, &forged), Err(WaterscapeError::Decoding(_))));
+    }
+
+    #[test]
+    fn test_commits_are_authenticated() {
+        let (agents, mut groups) = build(3);
+
+        // Commits travel hidden in cover text
+        let commit = groups[0].update(&agents[0]).unwrap();
+        let hidden = groups[0].encode_commit(COVER, &commit).unwrap();
+        let received = groups[1].decode_commit(&hidden).unwrap();
+
+        // A tampered commit is rejected and leaves the group unchanged
+        let mut tampered = received.clone();
+        tampered.path.leaf_key = [7u8; 32];
+        assert!(matches!(groups[1].process_commit(&tampered), Err(WaterscapeError::InvalidSignature)));
+        assert_eq!(groups[1].epoch(), 2);
+
+        groups[1].process_commit(&received).unwrap();
+        groups[2].process_commit(&received).unwrap();
+        assert!(groups[1].process_commit(&received).is_err());
+
+        // A path signed by a member but not matching its secrets is rejected
+        let mut forged = groups[2].update(&agents[2]).unwrap();
+        forged.path.nodes.last_mut().unwrap().public_key = [9u8; 32];
+        forged.signature = agents[2].signing_keypair().sign(&forged.transcript().unwrap()).to_bytes().to_vec();
+        assert!(matches!(groups[0].process_commit(&forged), Err(WaterscapeError::AuthenticationFailed)));
+        assert_eq!(groups[0].epoch(), 3);
+    }
+
+    #[test]
+    fn test_epoch_exhausted() {
+        let (agents, mut groups) = build(2);
+
+        // A signed commit for the last epoch cannot move the group past it
+        let mut commit = groups[0].update(&agents[0]).unwrap();
+        commit.epoch = u32::MAX;
+        commit.signature = agents[0].signing_keypair().sign(&commit.transcript().unwrap()).to_bytes().to_vec();
+        groups[1].epoch = u32::MAX;
+        match groups[1].process_commit(&commit) {
+            Err(WaterscapeError::Membership(reason)) => assert_eq!(reason, "Group epoch exhausted"),
+            other => panic!("Expected an exhausted epoch, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,211 @@
+//! Replay protection and message freshness checks
+//!
+//! Receivers reject messages whose payload timestamp falls outside a freshness
+//! window, and remember the `(sender, nonce)` pair of every accepted message so
+//! the same message cannot be accepted twice.
+//!
+//! The seen-message store is pluggable through [`SeenMessageCache`], so agents
+//! that restart can back it with persistent storage.
+
+use std::collections::HashMap;
+
+use crate::crypto::NONCE_SIZE;
+use crate::error::WaterscapeError;
+use crate::protocol::unix_timestamp_secs;
+use crate::Result;
+
+/// Default maximum message age (5 minutes, per the specification)
+pub const DEFAULT_MAX_AGE_SECS: u64 = 300;
+/// Default tolerance for sender clocks running ahead of ours
+pub const DEFAULT_MAX_CLOCK_SKEW_SECS: u64 = 30;
+
+/// Freshness window applied to decoded messages
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ReplayPolicy {
+    /// Reject messages older than this many seconds
+    pub max_age_secs: u64,
+    /// Accept messages timestamped up to this many seconds in the future
+    pub max_clock_skew_secs: u64,
+}
+
+impl Default for ReplayPolicy {
+    fn default() -> Self {
+        Self {
+            max_age_secs: DEFAULT_MAX_AGE_SECS,
+            max_clock_skew_secs: DEFAULT_MAX_CLOCK_SKEW_SECS,
+        }
+    }
+}
+
+impl ReplayPolicy {
+    /// Check a message timestamp against the window at time `now`
+    pub fn check_freshness(&self, timestamp: u64, now: u64) -> Result<()> {
+        let too_old = now.saturating_sub(timestamp) > self.max_age_secs;
+        let too_new = timestamp.saturating_sub(now) > self.max_clock_skew_secs;
+        if too_old || too_new {
+            return Err(WaterscapeError::StaleMessage { timestamp, now });
+        }
+        Ok(())
+    }
+}
+
+/// Unique identifier of a message: sender signing key and nonce
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct MessageId {
+    pub sender_key: [u8; 32],
+    pub nonce: [u8; NONCE_SIZE],
+}
+
+/// Store of already accepted messages
+pub trait SeenMessageCache {
+    /// Record a message; returns `false` if it had already been recorded
+    fn insert(&mut self, id: MessageId, timestamp: u64) -> bool;
+
+    /// Forget messages with a timestamp older than `cutoff`
+    ///
+    /// Called before every insert. Such messages are rejected as stale anyway,
+    /// so there is no need to remember them.
+    fn prune(&mut self, _cutoff: u64) {}
+}
+
+/// In-memory seen-message cache
+#[derive(Default)]
+pub struct MemorySeenCache {
+    entries: HashMap<MessageId, u64>,
+}
+
+impl MemorySeenCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of remembered messages
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+impl SeenMessageCache for MemorySeenCache {
+    fn insert(&mut self, id: MessageId, timestamp: u64) -> bool {
+        if self.entries.contains_key(&id) {
+            return false;
+        }
+        self.entries.insert(id, timestamp);
+        true
+    }
+
+    fn prune(&mut self, cutoff: u64) {
+        self.entries.retain(|_, &mut timestamp| timestamp >= cutoff);
+    }
+}
+
+/// Replay policy combined with a seen-message cache
+pub struct ReplayGuard {
+    policy: ReplayPolicy,
+    cache: Box<dyn SeenMessageCache + Send>,
+    clock: Box<dyn Fn() -> u64 + Send>,
+}
+
+impl Default for ReplayGuard {
+    fn default() -> Self {
+        Self::new(ReplayPolicy::default())
+    }
+}
+
+impl ReplayGuard {
+    /// Create a guard with an in-memory cache
+    pub fn new(policy: ReplayPolicy) -> Self {
+        Self::with_cache(policy, Box::new(MemorySeenCache::new()))
+    }
+
+    /// Create a guard with a custom cache
+    pub fn with_cache(policy: ReplayPolicy, cache: Box<dyn SeenMessageCache + Send>) -> Self {
+        Self {
+            policy,
+            cache,
+            clock: Box::new(unix_timestamp_secs),
+        }
+    }
+
+    /// Use a different source of the current Unix time in seconds
+    pub fn with_clock(mut self, clock: impl Fn() -> u64 + Send + 'static) -> Self {
+        self.clock = Box::new(clock);
+        self
+    }
+
+    pub fn policy(&self) -> &ReplayPolicy {
+        &self.policy
+    }
+
+    /// Check freshness and record the message as seen
+    pub fn check(&mut self, id: MessageId, timestamp: u64) -> Result<()> {
+        let now = (self.clock)();
+        self.check_at(id, timestamp, now)
+    }
+
+    /// Same as [`check`](Self::check) with an explicit current time
+    pub fn check_at(&mut self, id: MessageId, timestamp: u64, now: u64) -> Result<()> {
+        self.policy.check_freshness(timestamp, now)?;
+
+        self.cache.prune(now.saturating_sub(self.policy.max_age_secs));
+        if !self.cache.insert(id, timestamp) {
+            return Err(WaterscapeError::ReplayedMessage);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn id(byte: u8) -> MessageId {
+        MessageId {
+            sender_key: [1u8; 32],
+            nonce: [byte; NONCE_SIZE],
+        }
+    }
+
+    #[test]
+    fn test_freshness_window() {
+        let policy = ReplayPolicy::default();
+        let now = 1_000_000;
+
+        assert!(policy.check_freshness(now, now).is_ok());
+        assert!(policy.check_freshness(now - 300, now).is_ok());
+        assert!(policy.check_freshness(now + 30, now).is_ok());
+        assert!(matches!(
+            policy.check_freshness(now - 301, now),
+            Err(WaterscapeError::StaleMessage { .. })
+        ));
+        assert!(policy.check_freshness(now + 31, now).is_err());
+    }
+
+    #[test]
+    fn test_replay_rejected() {
+        let mut guard = ReplayGuard::default();
+        let now = 1_000_000;
+
+        assert!(guard.check_at(id(1), now, now).is_ok());
+        assert!(guard.check_at(id(2), now, now).is_ok());
+        assert!(matches!(
+            guard.check_at(id(1), now, now + 10),
+            Err(WaterscapeError::ReplayedMessage)
+        ));
+    }
+
+    #[test]
+    fn test_cache_pruned() {
+        let mut cache = MemorySeenCache::new();
+        assert!(cache.insert(id(1), 100));
+        assert!(cache.insert(id(2), 200));
+
+        cache.prune(150);
+        assert_eq!(cache.len(), 1);
+        assert!(cache.insert(id(1), 100));
+    }
+}
@@ -0,0 +1,590 @@
+//! Sender-keys group messaging
+//!
+//! Instead of encrypting every message under the shared group key, each member
+//! of a [`WaterscapeGroup`] owns a symmetric chain key. It hands the chain to the
+//! group once in a [`SenderKeyDistribution`], wrapped separately for each
+//! member's exchange key, and ratchets it forward for every message it sends:
+//!
+//! ```text
+//! message_key = HKDF(chain_key, "waterscape-v2-sender-message")
+//! chain_key'  = HKDF(chain_key, "waterscape-v2-sender-chain")
+//! ```
+//!
+//! Receivers keep one chain per sender and delete each message key after use,
+//! so a later copy of the chain state does not reveal messages already read.
+//! The distribution is wrapped to long-term exchange keys, though: whoever
+//! obtains a member's exchange key can unwrap recorded distributions and derive
+//! every message key of those chains. Membership, roles and epochs are those of
+//! the underlying group: chains belong to an epoch, and every member
+//! distributes a new chain after a re-key.
+//!
+//! Messages use the regular wire format. The ephemeral key field carries the
+//! epoch, chain id and iteration instead of an X25519 key.
+
+use std::collections::{BTreeMap, HashMap};
+
+use rand::rngs::OsRng;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use x25519_dalek::PublicKey as X25519PublicKey;
+use zeroize::Zeroize;
+
+use crate::agent::{Agent, PublicIdentity};
+use crate::crypto::{self, EphemeralKeyPair, SharedSecret, KEY_SIZE, NONCE_SIZE};
+use crate::error::WaterscapeError;
+use crate::protocol::{
+    cover_digest, group_id, push_str, unix_timestamp_secs, DecodedMessage, EncryptedPayload, WaterscapeGroup,
+    WaterscapeMessage, CONTEXT_PLACEMENT, PROTOCOL_VERSION,
+};
+use crate::Result;
+
+/// Version of the sender key distribution format
+pub const DISTRIBUTION_VERSION: u8 = 1;
+/// Most message keys a receiver derives ahead for messages that arrive out of order
+pub const MAX_SKIPPED_KEYS: usize = 256;
+
+const CONTEXT_CHAIN: &[u8] = b"waterscape-v2-sender-chain";
+const CONTEXT_MESSAGE: &[u8] = b"waterscape-v2-sender-message";
+const CONTEXT_WRAP: &[u8] = b"waterscape-v2-sender-key-wrap";
+const CONTEXT_DISTRIBUTION_SIGN: &[u8] = b"waterscape-v2-sender-key-sign";
+/// Last byte of the ephemeral key field of a sender-key message
+const SENDER_KEY_MARKER: u8 = 0x01;
+
+/// A chain key wrapped for one member
+///
+/// The wrapping key combines a fresh ephemeral X25519 key with the static
+/// exchange keys of the sender and the member, like a group key envelope.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct WrappedChainKey {
+    /// Exchange key of the member the chain key is for
+    #[serde(with = "hex::serde")]
+    pub recipient_key: [u8; 32],
+    #[serde(with = "hex::serde")]
+    pub ephemeral_key: [u8; 32],
+    #[serde(with = "hex::serde")]
+    pub nonce: [u8; NONCE_SIZE],
+    #[serde(with = "hex::serde")]
+    pub encrypted_chain_key: Vec<u8>,
+}
+
+impl WrappedChainKey {
+    /// Key that wraps the chain key for the recipient
+    fn wrapping_key(
+        ephemeral_secret: &SharedSecret,
+        static_secret: &SharedSecret,
+        ephemeral_key: &[u8; 32],
+        recipient_key: &[u8; 32],
+    ) -> [u8; KEY_SIZE] {
+        let mut context = Vec::with_capacity(CONTEXT_WRAP.len() + 64);
+        context.extend_from_slice(CONTEXT_WRAP);
+        context.extend_from_slice(ephemeral_key);
+        context.extend_from_slice(recipient_key);
+        ephemeral_secret.derive_key_with(static_secret, &context)
+    }
+
+    fn seal(sender: &Agent, recipient: &PublicIdentity, chain_key: &[u8; KEY_SIZE]) -> Result<Self> {
+        let recipient_key = X25519PublicKey::from(recipient.exchange_key);
+        let ephemeral = EphemeralKeyPair::generate();
+        let ephemeral_key = ephemeral.public_key_bytes();
+        let ephemeral_secret = ephemeral.diffie_hellman(&recipient_key);
+        let static_secret = sender.exchange_keypair().diffie_hellman(&recipient_key);
+        let mut key = Self::wrapping_key(&ephemeral_secret, &static_secret, &ephemeral_key, &recipient.exchange_key);
+        let nonce = crypto::generate_nonce();
+        let encrypted_chain_key = crypto::encrypt(&key, &nonce, chain_key);
+        key.zeroize();
+
+        Ok(Self {
+            recipient_key: recipient.exchange_key,
+            ephemeral_key,
+            nonce,
+            encrypted_chain_key: encrypted_chain_key?,
+        })
+    }
+
+    fn open(&self, receiver: &Agent, sender: &PublicIdentity) -> Result<[u8; KEY_SIZE]> {
+        let exchange = receiver.exchange_keypair();
+        let ephemeral_secret = exchange.diffie_hellman(&X25519PublicKey::from(self.ephemeral_key));
+        let static_secret = exchange.diffie_hellman(&X25519PublicKey::from(sender.exchange_key));
+        let mut key = Self::wrapping_key(&ephemeral_secret, &static_secret, &self.ephemeral_key, &self.recipient_key);
+        let chain_key = crypto::decrypt(&key, &self.nonce, &self.encrypted_chain_key);
+        key.zeroize();
+        let mut chain_key = chain_key?;
+        let parsed = <[u8; KEY_SIZE]>::try_from(chain_key.as_slice())
+            .map_err(|_| WaterscapeError::Crypto("Invalid chain key length".into()));
+        chain_key.zeroize();
+        parsed
+    }
+}
+
+/// A member's chain key, wrapped for every other member of its epoch
+///
+/// Each member can unwrap only its own copy, so holding the group key of
+/// `epoch` is not enough to read the chain. The distribution is signed by the
+/// sender's identity key.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SenderKeyDistribution {
+    pub version: u8,
+    pub group_name: String,
+    pub epoch: u32,
+    /// Signing key of the member who owns the chain
+    #[serde(with = "hex::serde")]
+    pub sender_key: [u8; 32],
+    /// Random identifier of the chain
+    pub chain_id: u32,
+    /// Iteration the wrapped chain key starts at
+    pub iteration: u32,
+    pub keys: Vec<WrappedChainKey>,
+    #[serde(with = "hex::serde")]
+    pub signature: Vec<u8>,
+}
+
+impl SenderKeyDistribution {
+    /// Serialize to JSON bytes
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        serde_json::to_vec(self).map_err(|e| WaterscapeError::Serialization(e.to_string()))
+    }
+
+    /// Deserialize from JSON bytes
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let distribution: Self = serde_json::from_slice(bytes)?;
+        if distribution.version != DISTRIBUTION_VERSION {
+            return Err(WaterscapeError::VersionMismatch {
+                expected: DISTRIBUTION_VERSION,
+                got: distribution.version,
+            });
+        }
+        Ok(distribution)
+    }
+
+    /// Canonical bytes signed by the sender
+    fn transcript(&self) -> Vec<u8> {
+        let mut transcript = CONTEXT_DISTRIBUTION_SIGN.to_vec();
+        transcript.push(self.version);
+        push_str(&mut transcript, &self.group_name);
+        transcript.extend_from_slice(&self.epoch.to_be_bytes());
+        transcript.extend_from_slice(&self.sender_key);
+        transcript.extend_from_slice(&self.chain_id.to_be_bytes());
+        transcript.extend_from_slice(&self.iteration.to_be_bytes());
+        transcript.extend_from_slice(&(self.keys.len() as u32).to_be_bytes());
+        for key in &self.keys {
+            transcript.extend_from_slice(&key.recipient_key);
+            transcript.extend_from_slice(&key.ephemeral_key);
+            transcript.extend_from_slice(&key.nonce);
+            transcript.extend_from_slice(&(key.encrypted_chain_key.len() as u32).to_be_bytes());
+            transcript.extend_from_slice(&key.encrypted_chain_key);
+        }
+        transcript
+    }
+}
+
+/// Ratcheting chain of one sender
+struct SenderChain {
+    epoch: u32,
+    chain_id: u32,
+    /// Iteration of the next message key
+    iteration: u32,
+    chain_key: [u8; KEY_SIZE],
+    /// Keys of messages skipped over, by iteration
+    skipped: BTreeMap<u32, [u8; KEY_SIZE]>,
+}
+
+impl SenderChain {
+    /// Derive the next message key and ratchet the chain forward
+    fn advance(&mut self) -> Result<(u32, [u8; KEY_SIZE])> {
+        let iteration = self.iteration;
+        self.iteration = iteration
+            .checked_add(1)
+            .ok_or_else(|| WaterscapeError::Crypto("Sender chain exhausted".into()))?;
+        let message_key = crypto::derive_from_seed(&self.chain_key, CONTEXT_MESSAGE);
+        let mut next = crypto::derive_from_seed(&self.chain_key, CONTEXT_CHAIN);
+        self.chain_key.zeroize();
+        self.chain_key = next;
+        next.zeroize();
+        Ok((iteration, message_key))
+    }
+
+    /// Take the message key of `iteration`, keeping keys skipped on the way
+    fn message_key(&mut self, iteration: u32) -> Result<[u8; KEY_SIZE]> {
+        if iteration < self.iteration {
+            // Each key is used once; a missing key means the message was already read
+            return self.skipped.remove(&iteration).ok_or(WaterscapeError::ReplayedMessage);
+        }
+        if (iteration - self.iteration) as usize + self.skipped.len() > MAX_SKIPPED_KEYS {
+            return Err(WaterscapeError::Decoding("Message is too far ahead of its sender chain".into()));
+        }
+        loop {
+            let (current, key) = self.advance()?;
+            if current == iteration {
+                return Ok(key);
+            }
+            self.skipped.insert(current, key);
+        }
+    }
+}
+
+/// Chains held for one sender
+struct SenderChains {
+    current: SenderChain,
+    /// Chain replaced by `current`, kept for messages still in flight until
+    /// `current` is first used
+    previous: Option<SenderChain>,
+}
+
+impl SenderChains {
+    fn holds(&self, epoch: u32, chain_id: u32) -> bool {
+        std::iter::once(&self.current)
+            .chain(&self.previous)
+            .any(|c| c.epoch == epoch && c.chain_id == chain_id)
+    }
+}
+
+impl Drop for SenderChain {
+    fn drop(&mut self) {
+        self.chain_key.zeroize();
+        for key in self.skipped.values_mut() {
+            key.zeroize();
+        }
+    }
+}
+
+/// Sender-key state carried in the ephemeral key field of a message
+fn chain_tag(epoch: u32, chain_id: u32, iteration: u32) -> [u8; 32] {
+    let mut tag = [0u8; 32];
+    tag[..4].copy_from_slice(&epoch.to_be_bytes());
+    tag[4..8].copy_from_slice(&chain_id.to_be_bytes());
+    tag[8..12].copy_from_slice(&iteration.to_be_bytes());
+    tag[31] = SENDER_KEY_MARKER;
+    tag
+}
+
+fn parse_chain_tag(tag: &[u8; 32]) -> Result<(u32, u32, u32)> {
+    if tag[31] != SENDER_KEY_MARKER || tag[12..31].iter().any(|&b| b != 0) {
+        return Err(WaterscapeError::Decoding("Not a sender-key message".into()));
+    }
+    let field = |i: usize| u32::from_be_bytes(tag[i..i + 4].try_into().expect("4-byte field"));
+    Ok((field(0), field(4), field(8)))
+}
+
+/// Sender-keys messaging over a [`WaterscapeGroup`]
+///
+/// Call [`distribute`](Self::distribute) after joining and after every re-key,
+/// and pass the distributions of other members to
+/// [`process_distribution`](Self::process_distribution). A sender cannot
+/// decode its own messages: the key of each one is gone once it is sent.
+pub struct SenderKeyGroup {
+    group: WaterscapeGroup,
+    own: Option<SenderChain>,
+    /// Chains of other members, by signing key
+    chains: HashMap<[u8; 32], SenderChains>,
+}
+
+impl SenderKeyGroup {
+    pub fn new(group: WaterscapeGroup) -> Self {
+        Self {
+            group,
+            own: None,
+            chains: HashMap::new(),
+        }
+    }
+
+    /// The underlying group, which handles membership and re-keying
+    pub fn group(&self) -> &WaterscapeGroup {
+        &self.group
+    }
+
+    pub fn group_mut(&mut self) -> &mut WaterscapeGroup {
+        &mut self.group
+    }
+
+    /// Start a fresh chain for the current epoch and wrap it for every other member
+    pub fn distribute(&mut self, sender: &Agent) -> Result<SenderKeyDistribution> {
+        let sender_key = sender.public_identity().signing_key;
+        let epoch = self.group.epoch();
+        self.group.authorized_sender(epoch, &sender_key)?;
+
+        let chain = SenderChain {
+            epoch,
+            chain_id: OsRng.next_u32(),
+            iteration: 0,
+            chain_key: crypto::generate_key(),
+            skipped: BTreeMap::new(),
+        };
+
+        let keys = self
+            .group
+            .members()
+            .iter()
+            .filter(|m| m.identity.signing_key != sender_key)
+            .map(|m| WrappedChainKey::seal(sender, &m.identity, &chain.chain_key))
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut distribution = SenderKeyDistribution {
+            version: DISTRIBUTION_VERSION,
+            group_name: self.group.name().to_string(),
+            epoch,
+            sender_key,
+            chain_id: chain.chain_id,
+            iteration: chain.iteration,
+            keys,
+            signature: Vec::new(),
+        };
+        distribution.signature = sender.signing_keypair().sign(&distribution.transcript()).to_bytes().to_vec();
+        self.own = Some(chain);
+        Ok(distribution)
+    }
+
+    /// Verify another member's distribution and start tracking its chain
+    ///
+    /// The sender must have been allowed to post in the distribution's epoch. A
+    /// chain already held, or one from an older epoch than the chain held, is
+    /// ignored, so redelivering a distribution cannot rewind the chain. The
+    /// replaced chain stays usable until the first message on the new one.
+    pub fn process_distribution(&mut self, receiver: &Agent, distribution: &SenderKeyDistribution) -> Result<()> {
+        if distribution.group_name != self.group.name() {
+            return Err(WaterscapeError::KeyExchange("Distribution is for another group".into()));
+        }
+        let epoch = distribution.epoch;
+        self.group.epoch_key(epoch)?;
+        let sender = self.group.authorized_sender(epoch, &distribution.sender_key)?;
+        let signature: [u8; 64] = distribution.signature.clone().try_into()
+            .map_err(|_| WaterscapeError::Crypto("Invalid signature length".into()))?;
+        crypto::verify_signature(
+            &sender.identity.signing_key,
+            &distribution.transcript(),
+            &ed25519_dalek::Signature::from_bytes(&signature),
+        )?;
+
+        let held = self.chains.get(&distribution.sender_key);
+        if held.is_some_and(|c| c.current.epoch > epoch || c.holds(epoch, distribution.chain_id)) {
+            return Ok(());
+        }
+
+        let receiver_key = receiver.public_identity().exchange_key;
+        let wrapped = distribution
+            .keys
+            .iter()
+            .find(|k| k.recipient_key == receiver_key)
+            .ok_or_else(|| WaterscapeError::KeyExchange("Distribution has no chain key for this agent".into()))?;
+        let chain = SenderChain {
+            epoch,
+            chain_id: distribution.chain_id,
+            iteration: distribution.iteration,
+            chain_key: wrapped.open(receiver, &sender.identity)?,
+            skipped: BTreeMap::new(),
+        };
+
+        let previous = self.chains.remove(&distribution.sender_key).map(|c| c.current);
+        self.chains.insert(distribution.sender_key, SenderChains { current: chain, previous });
+        Ok(())
+    }
+
+    /// Encode a message under the next key of this member's chain
+    pub fn encode(&mut self, sender: &Agent, cover_text: &str, secret: &str) -> Result<String> {
+        let sender_key = sender.public_identity().signing_key;
+        let epoch = self.group.epoch();
+        match self.group.role(&sender_key) {
+            Some(role) if role.can_post() => {}
+            _ => return Err(WaterscapeError::Unauthorized),
+        }
+        let chain = match self.own.as_mut() {
+            Some(chain) if chain.epoch == epoch => chain,
+            _ => {
+                return Err(WaterscapeError::KeyExchange(
+                    "No sender chain for the current epoch; distribute one first".into(),
+                ))
+            }
+        };
+        let chain_id = chain.chain_id;
+        let (iteration, mut message_key) = chain.advance()?;
+
+        let payload = EncryptedPayload {
+            content: secret.to_string(),
+            timestamp: unix_timestamp_secs(),
+            metadata: Some(self.group.name().to_string()),
+            cover_hash: self.group.binds_cover().then(|| cover_digest(cover_text)),
+        };
+        let nonce = crypto::generate_nonce();
+        let ciphertext = crypto::encrypt(&message_key, &nonce, &payload.to_bytes()?);
+        let mut placement_key = crypto::derive_from_seed(&message_key, CONTEXT_PLACEMENT);
+        message_key.zeroize();
+
+        let mut message = WaterscapeMessage {
+            version: PROTOCOL_VERSION,
+            nonce,
+            sender_key,
+            ephemeral_key: chain_tag(epoch, chain_id, iteration),
+            ciphertext: ciphertext?,
+            signature: Vec::new(),
+        };
+        let signature = sender.signing_keypair().sign(&message.transcript(&group_id(self.group.epoch_key(epoch)?)));
+        message.signature = signature.to_bytes().to_vec();
+
+        let encoded = self.group.codec().hide_keyed(cover_text, &message.to_bytes()?, &placement_key);
+        placement_key.zeroize();
+        encoded
+    }
+
+    /// Decode a message, returning its sender and the state of its cover text
+    ///
+    /// Each message can be decoded once; its key is deleted afterwards.
+    pub fn decode(&mut self, text: &str) -> Result<DecodedMessage> {
+        let message_bytes = self.group.codec().extract(text)?;
+        let message = WaterscapeMessage::from_bytes(&message_bytes)?;
+        message.check_version(false)?;
+        let (epoch, chain_id, iteration) = parse_chain_tag(&message.ephemeral_key)?;
+
+        message.verify_signature(&group_id(self.group.epoch_key(epoch)?))?;
+        let sender = self.group.authorized_sender(epoch, &message.sender_key)?.identity.clone();
+
+        let unknown = || WaterscapeError::UnknownSenderChain {
+            sender: sender.fingerprint(),
+        };
+        let chains = self.chains.get_mut(&message.sender_key).ok_or_else(unknown)?;
+        let matches = |c: &SenderChain| c.epoch == epoch && c.chain_id == chain_id;
+        let mut message_key = if matches(&chains.current) {
+            let key = chains.current.message_key(iteration)?;
+            // The sender has moved on; nothing more arrives on the old chain
+            chains.previous = None;
+            key
+        } else {
+            chains.previous.as_mut().filter(|c| matches(c)).ok_or_else(unknown)?.message_key(iteration)?
+        };
+        let payload = crypto::decrypt(&message_key, &message.nonce, &message.ciphertext);
+        message_key.zeroize();
+        let payload = EncryptedPayload::from_bytes(&payload?)?;
+
+        Ok(DecodedMessage::new(&message, sender, payload, &self.group.codec().strip(text)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::{GroupMember, GroupRole};
+
+    fn cover() -> &'static str {
+        "Sprint review moved to Thursday; demo notes and slides are in the shared folder as usual."
+    }
+
+    /// Alice's group with Bob and Charlie, each in sender-keys mode
+    fn setup(alice: &Agent, bob: &Agent, charlie: &Agent) -> [SenderKeyGroup; 3] {
+        let group = WaterscapeGroup::new("swarm", alice, vec![bob.public_identity(), charlie.public_identity()]);
+        let envelopes = group.envelopes(alice).unwrap();
+        let bob_group = WaterscapeGroup::from_envelope(bob, &envelopes[0]).unwrap();
+        let charlie_group = WaterscapeGroup::from_envelope(charlie, &envelopes[1]).unwrap();
+        [SenderKeyGroup::new(group), SenderKeyGroup::new(bob_group), SenderKeyGroup::new(charlie_group)]
+    }
+
+    #[test]
+    fn test_sender_chains() {
+        let (alice, bob, charlie) = (Agent::new("alice"), Agent::new("bob"), Agent::new("charlie"));
+        let [mut alice_group, mut bob_group, mut charlie_group] = setup(&alice, &bob, &charlie);
+
+        let distribution = alice_group.distribute(&alice).unwrap();
+        let distribution = SenderKeyDistribution::from_bytes(&distribution.to_bytes().unwrap()).unwrap();
+        bob_group.process_distribution(&bob, &distribution).unwrap();
+        charlie_group.process_distribution(&charlie, &distribution).unwrap();
+
+        let first = alice_group.encode(&alice, cover(), "first").unwrap();
+        let second = alice_group.encode(&alice, cover(), "second").unwrap();
+        let third = alice_group.encode(&alice, cover(), "third").unwrap();
+
+        // Every message uses its own key
+        let iterations: Vec<u32> = [&first, &second, &third]
+            .iter()
+            .map(|text| {
+                let bytes = alice_group.group().codec().extract(text).unwrap();
+                parse_chain_tag(&WaterscapeMessage::from_bytes(&bytes).unwrap().ephemeral_key).unwrap().2
+            })
+            .collect();
+        assert_eq!(iterations, [0, 1, 2]);
+
+        // Out of order delivery works, and each message opens once
+        let decoded = bob_group.decode(&third).unwrap();
+        assert_eq!((decoded.content.as_str(), decoded.sender.name.as_str()), ("third", "alice"));
+        assert_eq!(bob_group.decode(&first).unwrap().content, "first");
+        assert_eq!(bob_group.decode(&second).unwrap().content, "second");
+        assert!(matches!(bob_group.decode(&second), Err(WaterscapeError::ReplayedMessage)));
+        bob_group.process_distribution(&bob, &distribution).unwrap();
+        assert!(matches!(bob_group.decode(&first), Err(WaterscapeError::ReplayedMessage)));
+        assert_eq!(charlie_group.decode(&first).unwrap().content, "first");
+
+        // The shared group key alone does not open sender-key messages
+        assert!(alice_group.group().decode(&first).is_err());
+        assert!(matches!(
+            charlie_group.process_distribution(&charlie, &SenderKeyDistribution {
+                iteration: 5,
+                ..distribution.clone()
+            }),
+            Err(WaterscapeError::InvalidSignature)
+        ));
+
+        // Holding the group key is not enough to unwrap a chain
+        let mallory = Agent::new("mallory");
+        let mut members = alice_group.group().members().to_vec();
+        members.push(GroupMember::new(mallory.public_identity(), GroupRole::Member));
+        let stolen = WaterscapeGroup::with_key("swarm", members, 0, *alice_group.group().epoch_key(0).unwrap());
+        assert!(matches!(
+            SenderKeyGroup::new(stolen).process_distribution(&mallory, &distribution),
+            Err(WaterscapeError::KeyExchange(_))
+        ));
+
+        // Senders are separated: Bob's messages need Bob's chain
+        bob_group.distribute(&bob).unwrap();
+        let from_bob = bob_group.encode(&bob, cover(), "from bob").unwrap();
+        assert!(matches!(charlie_group.decode(&from_bob), Err(WaterscapeError::UnknownSenderChain { .. })));
+    }
+
+    #[test]
+    fn test_redistribution_keeps_messages_in_flight() {
+        let (alice, bob, charlie) = (Agent::new("alice"), Agent::new("bob"), Agent::new("charlie"));
+        let [mut alice_group, mut bob_group, _] = setup(&alice, &bob, &charlie);
+        bob_group.process_distribution(&bob, &alice_group.distribute(&alice).unwrap()).unwrap();
+        let early = alice_group.encode(&alice, cover(), "early").unwrap();
+        let in_flight = alice_group.encode(&alice, cover(), "in flight").unwrap();
+
+        // Alice starts a new chain in the same epoch before Bob reads the old messages
+        let distribution = alice_group.distribute(&alice).unwrap();
+        let fresh = alice_group.encode(&alice, cover(), "fresh").unwrap();
+        bob_group.process_distribution(&bob, &distribution).unwrap();
+        assert_eq!(bob_group.decode(&in_flight).unwrap().content, "in flight");
+
+        // The old chain is dropped once the new one is used
+        assert_eq!(bob_group.decode(&fresh).unwrap().content, "fresh");
+        assert!(matches!(bob_group.decode(&early), Err(WaterscapeError::UnknownSenderChain { .. })));
+    }
+
+    #[test]
+    fn test_sender_chains_follow_epochs() {
+        let (alice, bob, charlie) = (Agent::new("alice"), Agent::new("bob"), Agent::new("charlie"));
+        let [mut alice_group, mut bob_group, mut charlie_group] = setup(&alice, &bob, &charlie);
+        let distribution = alice_group.distribute(&alice).unwrap();
+        charlie_group.process_distribution(&charlie, &distribution).unwrap();
+
+        // Removing Charlie moves the group to a new epoch; the old chain stops working
+        let envelopes = alice_group
+            .group_mut()
+            .remove_member(&alice, &charlie.public_identity().signing_key)
+            .unwrap();
+        bob_group.group_mut().apply_envelope(&bob, &envelopes[0]).unwrap();
+        assert!(alice_group.encode(&alice, cover(), "stale").is_err());
+
+        let distribution = alice_group.distribute(&alice).unwrap();
+        assert!(matches!(
+            charlie_group.process_distribution(&charlie, &distribution),
+            Err(WaterscapeError::UnknownEpoch { epoch: 1 })
+        ));
+        bob_group.process_distribution(&bob, &distribution).unwrap();
+        let encoded = alice_group.encode(&alice, cover(), "epoch one").unwrap();
+        assert_eq!(bob_group.decode(&encoded).unwrap().content, "epoch one");
+        assert!(charlie_group.decode(&encoded).is_err());
+
+        // Read-only members neither distribute chains nor post
+        let envelopes = alice_group
+            .group_mut()
+            .set_role(&alice, &bob.public_identity().signing_key, GroupRole::ReadOnly)
+            .unwrap();
+        bob_group.group_mut().apply_envelope(&bob, &envelopes[0]).unwrap();
+        assert!(matches!(bob_group.distribute(&bob), Err(WaterscapeError::SenderNotPermitted { .. })));
+    }
+}
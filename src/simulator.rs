@@ -0,0 +1,333 @@
+//! Platform transformation simulator
+//!
+//! Applies the kinds of rewriting that posting platforms perform on submitted text
+//! (Unicode normalization, stripping invisible characters, whitespace collapsing,
+//! truncation, HTML escaping, Markdown rendering) and reports which stego carriers
+//! still deliver their payload afterwards.
+//!
+//! ```
+//! use waterscape::simulator::{survivability_matrix, Carrier, Transform};
+//!
+//! let cover = "A perfectly ordinary comment about the weather.\nSecond line.";
+//! let matrix = survivability_matrix(cover, b"payload", &Carrier::all(), &Transform::standard());
+//! println!("{}", matrix);
+//! ```
+
+use std::fmt;
+
+use unicode_normalization::UnicodeNormalization;
+
+use crate::stego::fec::FecCodec;
+use crate::stego::homoglyph::HomoglyphCodec;
+use crate::stego::tags::TagCodec;
+use crate::stego::variation::VariationSelectorCodec;
+use crate::stego::whitespace::WhitespaceCodec;
+use crate::stego::{StegoCodec, ZeroWidthCodec, ZeroWidthEncoding};
+
+/// A text rewrite performed by a platform
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Transform {
+    /// Text is stored unchanged (baseline)
+    Identity,
+    /// Unicode canonical composition
+    Nfc,
+    /// Unicode compatibility composition
+    Nfkc,
+    /// Removal of zero-width and invisible formatting characters
+    StripZeroWidth,
+    /// Every whitespace run (including line breaks) becomes one space
+    CollapseWhitespace,
+    /// Only the first N characters are kept
+    Truncate(usize),
+    /// `&`, `<`, `>`, `"` and `'` are replaced by HTML entities
+    HtmlEscape,
+    /// Approximation of Markdown rendered to plain text
+    ///
+    /// Trailing whitespace is trimmed, soft line breaks inside a paragraph are
+    /// joined with a space, and U+200D / U+FEFF are dropped as several renderers do.
+    MarkdownRender,
+    /// Several transforms applied in order
+    Chain(Vec<Transform>),
+}
+
+impl Transform {
+    /// Transforms covering the common platform behaviours
+    pub fn standard() -> Vec<Transform> {
+        vec![
+            Transform::Identity,
+            Transform::Nfc,
+            Transform::Nfkc,
+            Transform::StripZeroWidth,
+            Transform::CollapseWhitespace,
+            Transform::Truncate(280),
+            Transform::HtmlEscape,
+            Transform::MarkdownRender,
+        ]
+    }
+
+    /// Short name used as a column heading
+    pub fn name(&self) -> String {
+        match self {
+            Transform::Identity => "identity".into(),
+            Transform::Nfc => "nfc".into(),
+            Transform::Nfkc => "nfkc".into(),
+            Transform::StripZeroWidth => "strip-zero-width".into(),
+            Transform::CollapseWhitespace => "collapse-whitespace".into(),
+            Transform::Truncate(len) => format!("truncate-{}", len),
+            Transform::HtmlEscape => "html-escape".into(),
+            Transform::MarkdownRender => "markdown".into(),
+            Transform::Chain(transforms) => transforms.iter().map(Transform::name).collect::<Vec<_>>().join("+"),
+        }
+    }
+
+    /// Apply the transform to text
+    pub fn apply(&self, text: &str) -> String {
+        match self {
+            Transform::Identity => text.to_string(),
+            Transform::Nfc => text.nfc().collect(),
+            Transform::Nfkc => text.nfkc().collect(),
+            Transform::StripZeroWidth => text.chars().filter(|&c| !is_zero_width(c)).collect(),
+            Transform::CollapseWhitespace => text.split_whitespace().collect::<Vec<_>>().join(" "),
+            Transform::Truncate(len) => text.chars().take(*len).collect(),
+            Transform::HtmlEscape => html_escape(text),
+            Transform::MarkdownRender => render_markdown(text),
+            Transform::Chain(transforms) => transforms
+                .iter()
+                .fold(text.to_string(), |text, transform| transform.apply(&text)),
+        }
+    }
+}
+
+/// Characters removed by platforms that strip zero-width content
+fn is_zero_width(c: char) -> bool {
+    matches!(c, '\u{00AD}' | '\u{180E}' | '\u{200B}'..='\u{200F}' | '\u{2060}'..='\u{206F}' | '\u{FEFF}')
+}
+
+fn html_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn render_markdown(text: &str) -> String {
+    text.split("\n\n")
+        .map(|paragraph| {
+            paragraph
+                .lines()
+                .map(|line| line.trim_end())
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n")
+        .chars()
+        .filter(|&c| c != '\u{200D}' && c != '\u{FEFF}')
+        .collect()
+}
+
+/// A codec under test, with a label distinguishing its configuration
+pub struct Carrier {
+    pub label: String,
+    pub codec: Box<dyn StegoCodec>,
+}
+
+impl Carrier {
+    pub fn new(label: impl Into<String>, codec: impl StegoCodec + 'static) -> Self {
+        Self {
+            label: label.into(),
+            codec: Box::new(codec),
+        }
+    }
+
+    /// Every carrier and zero-width encoding shipped with this crate
+    pub fn all() -> Vec<Carrier> {
+        vec![
+            Carrier::new("zero-width/binary", ZeroWidthCodec::new(ZeroWidthEncoding::Binary)),
+            Carrier::new("zero-width/base4", ZeroWidthCodec::new(ZeroWidthEncoding::Base4)),
+            Carrier::new("zero-width/base8", ZeroWidthCodec::new(ZeroWidthEncoding::Base8)),
+            Carrier::new("zero-width-fec", FecCodec::default()),
+            Carrier::new("unicode-tags", TagCodec),
+            Carrier::new("variation-selectors", VariationSelectorCodec),
+            Carrier::new("trailing-whitespace", WhitespaceCodec),
+            Carrier::new("homoglyphs", HomoglyphCodec),
+        ]
+    }
+}
+
+/// Result of passing one carrier through one transform
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Survival {
+    /// The payload was extracted unchanged
+    Intact,
+    /// Hidden data is still detected but cannot be extracted correctly
+    Damaged,
+    /// No hidden data is detected any more
+    Lost,
+    /// The carrier could not embed the payload in the cover text
+    NotEmbedded,
+}
+
+impl Survival {
+    fn symbol(self) -> &'static str {
+        match self {
+            Survival::Intact => "ok",
+            Survival::Damaged => "damaged",
+            Survival::Lost => "lost",
+            Survival::NotEmbedded => "n/a",
+        }
+    }
+}
+
+/// Hide `data` with a codec, apply a transform and check what is left
+pub fn simulate(codec: &dyn StegoCodec, cover_text: &str, data: &[u8], transform: &Transform) -> Survival {
+    let Ok(stego_text) = codec.hide(cover_text, data) else {
+        return Survival::NotEmbedded;
+    };
+    let transformed = transform.apply(&stego_text);
+
+    match codec.extract(&transformed) {
+        Ok(extracted) if extracted == data => Survival::Intact,
+        _ if codec.detect(&transformed) => Survival::Damaged,
+        _ => Survival::Lost,
+    }
+}
+
+/// Survival of every carrier under every transform
+#[derive(Clone, Debug)]
+pub struct SurvivabilityMatrix {
+    pub carriers: Vec<String>,
+    pub transforms: Vec<String>,
+    /// `results[carrier][transform]`
+    pub results: Vec<Vec<Survival>>,
+}
+
+impl SurvivabilityMatrix {
+    /// Look up a cell by carrier label and transform name
+    pub fn get(&self, carrier: &str, transform: &str) -> Option<Survival> {
+        let row = self.carriers.iter().position(|c| c == carrier)?;
+        let column = self.transforms.iter().position(|t| t == transform)?;
+        Some(self.results[row][column])
+    }
+
+    /// Carriers that come through the given transform intact
+    pub fn surviving(&self, transform: &str) -> Vec<&str> {
+        self.carriers
+            .iter()
+            .filter(|carrier| self.get(carrier, transform) == Some(Survival::Intact))
+            .map(String::as_str)
+            .collect()
+    }
+}
+
+impl fmt::Display for SurvivabilityMatrix {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label_width = self.carriers.iter().map(String::len).max().unwrap_or(0);
+        write!(f, "{:label_width$}", "")?;
+        for transform in &self.transforms {
+            write!(f, " | {}", transform)?;
+        }
+        writeln!(f)?;
+
+        for (carrier, row) in self.carriers.iter().zip(&self.results) {
+            write!(f, "{:label_width$}", carrier)?;
+            for (transform, survival) in self.transforms.iter().zip(row) {
+                write!(f, " | {:width$}", survival.symbol(), width = transform.len())?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// Run every carrier through every transform
+pub fn survivability_matrix(
+    cover_text: &str,
+    data: &[u8],
+    carriers: &[Carrier],
+    transforms: &[Transform],
+) -> SurvivabilityMatrix {
+    SurvivabilityMatrix {
+        carriers: carriers.iter().map(|c| c.label.clone()).collect(),
+        transforms: transforms.iter().map(Transform::name).collect(),
+        results: carriers
+            .iter()
+            .map(|carrier| {
+                transforms
+                    .iter()
+                    .map(|transform| simulate(carrier.codec.as_ref(), cover_text, data, transform))
+                    .collect()
+            })
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const COVER: &str = "Shipping the release tonight, assuming the CI pipeline cooperates.\n\
+                         Anyone who wants to review the changelog please do so soon.\n\
+                         Otherwise we go ahead as planned and sort out issues afterwards.";
+
+    #[test]
+    fn test_transforms() {
+        assert_eq!(Transform::CollapseWhitespace.apply("a \t b\n\nc "), "a b c");
+        assert_eq!(Transform::Truncate(3).apply("héllo"), "hél");
+        assert_eq!(Transform::HtmlEscape.apply("<a & 'b'>"), "&lt;a &amp; &#39;b&#39;&gt;");
+        assert_eq!(Transform::StripZeroWidth.apply("a\u{200B}b\u{FEFF}"), "ab");
+        assert_eq!(Transform::Nfkc.apply("\u{FB01}"), "fi");
+        assert_eq!(Transform::MarkdownRender.apply("one  \ntwo\n\nthree"), "one two\n\nthree");
+        assert_eq!(
+            Transform::Chain(vec![Transform::Nfc, Transform::Truncate(4)]).name(),
+            "nfc+truncate-4"
+        );
+    }
+
+    #[test]
+    fn test_survivability_matrix() {
+        let matrix = survivability_matrix(COVER, b"go", &Carrier::all(), &Transform::standard());
+
+        for carrier in &matrix.carriers {
+            assert_eq!(matrix.get(carrier, "identity"), Some(Survival::Intact), "{}", carrier);
+        }
+
+        let survivors = matrix.surviving("strip-zero-width");
+        assert!(!survivors.contains(&"zero-width/base8"));
+        assert!(survivors.contains(&"unicode-tags"));
+        assert!(survivors.contains(&"trailing-whitespace"));
+
+        assert_eq!(matrix.get("trailing-whitespace", "collapse-whitespace"), Some(Survival::Lost));
+        assert_eq!(matrix.get("homoglyphs", "nfkc"), Some(Survival::Intact));
+        assert_eq!(matrix.get("unicode-tags", "markdown"), Some(Survival::Intact));
+        assert_eq!(matrix.get("zero-width/base8", "markdown"), Some(Survival::Lost));
+
+        let table = matrix.to_string();
+        assert_eq!(table.lines().count(), matrix.carriers.len() + 1);
+    }
+
+    #[test]
+    fn test_message_survival() {
+        use crate::agent::Agent;
+        use crate::protocol::Waterscape;
+
+        // Full encrypted messages through the simulator, as a test harness would use it
+        let alice = Agent::new("alice");
+        let bob = Agent::new("bob");
+        let encoded =
+            Waterscape::encode_with_codec(TagCodec, &alice, &bob.public_identity(), COVER, "deploy").unwrap();
+
+        for transform in [Transform::Nfc, Transform::StripZeroWidth, Transform::HtmlEscape] {
+            let decoded = Waterscape::decode(&bob, &alice.public_identity(), &transform.apply(&encoded));
+            assert_eq!(decoded.unwrap(), "deploy", "{}", transform.name());
+        }
+    }
+}
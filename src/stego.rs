@@ -4,21 +4,143 @@
 //! The encoding is invisible to humans but can be detected and decoded by agents.
 //!
 //! ## Encoding scheme
-//! - Zero-width space (U+200B) = 0
-//! - Zero-width non-joiner (U+200C) = 1
-//! - Zero-width joiner (U+200D) = separator between bytes
+//! Hidden data is framed by a start marker (U+2060) and an end marker (U+FEFF).
+//! The first character after the start marker selects the symbol alphabet:
 //!
+//! - U+2063: [`ZeroWidthEncoding::Base8`], 8 symbols carrying 3 bits each
+//! - U+2062: [`ZeroWidthEncoding::Base4`], 4 symbols carrying 2 bits each
+//! - anything else: [`ZeroWidthEncoding::Binary`], the original scheme:
+//!   - Zero-width space (U+200B) = 0
+//!   - Zero-width non-joiner (U+200C) = 1
+//!   - Zero-width joiner (U+200D) = separator between bytes
+//!
+//! The denser alphabets pack bits across byte boundaries without separators.
 //! Hidden data is inserted after each visible character in the cover text.
+//!
+//! Alternative carriers live in submodules ([`tags`], [`variation`], [`whitespace`],
+//! [`homoglyph`]). The text-level helpers below (`extract_from_text`,
+//! `extract_visible_text`, `has_hidden_data`) recognise all of them.
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::Serialize;
 
 use crate::error::WaterscapeError;
 use crate::Result;
 
+pub mod fec;
+pub mod homoglyph;
+pub mod tags;
+pub mod variation;
+pub mod whitespace;
+
 const ZERO: char = '\u{200B}'; // Zero-width space = 0
 const ONE: char = '\u{200C}';  // Zero-width non-joiner = 1
 const SEP: char = '\u{200D}';  // Zero-width joiner = byte separator
 const MARKER_START: &str = "\u{2060}"; // Word joiner - marks start of hidden data
 const MARKER_END: &str = "\u{FEFF}";   // Zero-width no-break space - marks end
 
+const VERSION_BASE4: char = '\u{2062}'; // Invisible times
+const VERSION_BASE8: char = '\u{2063}'; // Invisible separator
+
+const BASE4_ALPHABET: [char; 4] = ['\u{200B}', '\u{200C}', '\u{200D}', '\u{2061}'];
+// The version symbols double as Base8 symbols: only the first body character
+// selects the alphabet. U+206A..U+206F are deprecated and deliberately avoided,
+// and the eighth symbol is an invisible format character (musical begin beam)
+// that ordinary text does not use.
+const BASE8_ALPHABET: [char; 8] = [
+    '\u{200B}', '\u{200C}', '\u{200D}', '\u{2061}',
+    '\u{2062}', '\u{2063}', '\u{2064}', '\u{1D173}',
+];
+
+/// Symbol alphabet used to encode bytes between the markers
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ZeroWidthEncoding {
+    /// Two bit symbols plus a separator per byte (9 characters per byte)
+    Binary,
+    /// Four symbols carrying 2 bits each (4 characters per byte)
+    Base4,
+    /// Eight symbols carrying 3 bits each (about 2.7 characters per byte)
+    #[default]
+    Base8,
+}
+
+impl ZeroWidthEncoding {
+    /// Character written after the start marker, if any
+    fn version_symbol(self) -> Option<char> {
+        match self {
+            Self::Binary => None,
+            Self::Base4 => Some(VERSION_BASE4),
+            Self::Base8 => Some(VERSION_BASE8),
+        }
+    }
+
+    fn from_version_symbol(c: char) -> Self {
+        match c {
+            VERSION_BASE4 => Self::Base4,
+            VERSION_BASE8 => Self::Base8,
+            _ => Self::Binary,
+        }
+    }
+
+    /// Number of zero-width characters needed for `len` bytes, including markers
+    pub fn encoded_len(self, len: usize) -> usize {
+        match self {
+            Self::Binary => len * 9 + 2,
+            Self::Base4 => (len * 8).div_ceil(2) + 3,
+            Self::Base8 => (len * 8).div_ceil(3) + 3,
+        }
+    }
+}
+
+/// Check if a character belongs to the zero-width encoding
+fn is_encoding_char(c: char) -> bool {
+    matches!(
+        c,
+        ZERO | ONE | SEP | '\u{2060}' | '\u{FEFF}' | VERSION_BASE4 | VERSION_BASE8
+    ) || BASE8_ALPHABET.contains(&c)
+}
+
+/// Pack bytes into symbols of `bits` bits each, most significant bit first
+fn encode_symbols(data: &[u8], alphabet: &[char], bits: u32, out: &mut String) {
+    let mask = (1u32 << bits) - 1;
+    let mut acc = 0u32;
+    let mut acc_bits = 0u32;
+
+    for &byte in data {
+        acc = (acc << 8) | byte as u32;
+        acc_bits += 8;
+        while acc_bits >= bits {
+            acc_bits -= bits;
+            out.push(alphabet[((acc >> acc_bits) & mask) as usize]);
+        }
+    }
+    if acc_bits > 0 {
+        out.push(alphabet[((acc << (bits - acc_bits)) & mask) as usize]);
+    }
+}
+
+/// Unpack symbols of `bits` bits each back into bytes, dropping padding bits
+fn decode_symbols(chars: &[char], alphabet: &[char], bits: u32) -> Result<Vec<u8>> {
+    let mut result = Vec::with_capacity(chars.len() * bits as usize / 8);
+    let mut acc = 0u32;
+    let mut acc_bits = 0u32;
+
+    for &c in chars {
+        let value = alphabet
+            .iter()
+            .position(|&a| a == c)
+            .ok_or_else(|| WaterscapeError::Decoding(format!("Invalid symbol character: {:?}", c)))?;
+        acc = ((acc << bits) | value as u32) & 0xFFFF;
+        acc_bits += bits;
+        if acc_bits >= 8 {
+            acc_bits -= 8;
+            result.push((acc >> acc_bits) as u8);
+        }
+    }
+    Ok(result)
+}
+
 /// Encode binary data into zero-width characters
 fn encode_byte(byte: u8) -> String {
     let mut result = String::with_capacity(9);
@@ -51,12 +173,26 @@ fn decode_byte(chars: &[char]) -> Result<u8> {
     Ok(byte)
 }
 
-/// Encode binary data as zero-width string
+/// Encode binary data as zero-width string using the default encoding
 pub fn encode_data(data: &[u8]) -> String {
-    let mut result = String::with_capacity(data.len() * 9 + 2);
+    encode_data_with(data, ZeroWidthEncoding::default())
+}
+
+/// Encode binary data as zero-width string using a specific encoding
+pub fn encode_data_with(data: &[u8], encoding: ZeroWidthEncoding) -> String {
+    let mut result = String::with_capacity(encoding.encoded_len(data.len()) * 3);
     result.push_str(MARKER_START);
-    for &byte in data {
-        result.push_str(&encode_byte(byte));
+    if let Some(version) = encoding.version_symbol() {
+        result.push(version);
+    }
+    match encoding {
+        ZeroWidthEncoding::Binary => {
+            for &byte in data {
+                result.push_str(&encode_byte(byte));
+            }
+        }
+        ZeroWidthEncoding::Base4 => encode_symbols(data, &BASE4_ALPHABET, 2, &mut result),
+        ZeroWidthEncoding::Base8 => encode_symbols(data, &BASE8_ALPHABET, 3, &mut result),
     }
     result.push_str(MARKER_END);
     result
@@ -77,9 +213,19 @@ pub fn decode_data(encoded: &str) -> Result<Vec<u8>> {
     if end_pos <= start_pos {
         return Err(WaterscapeError::NoHiddenMessage);
     }
-    
+
+    let body = &chars[start_pos + 1..end_pos];
+    let encoding = body
+        .first()
+        .map_or(ZeroWidthEncoding::Binary, |&c| ZeroWidthEncoding::from_version_symbol(c));
+    match encoding {
+        ZeroWidthEncoding::Binary => {}
+        ZeroWidthEncoding::Base4 => return decode_symbols(&body[1..], &BASE4_ALPHABET, 2),
+        ZeroWidthEncoding::Base8 => return decode_symbols(&body[1..], &BASE8_ALPHABET, 3),
+    }
+
     // Extract only zero-width bit characters between markers
-    let bit_chars: Vec<char> = chars[start_pos + 1..end_pos]
+    let bit_chars: Vec<char> = body
         .iter()
         .filter(|&&c| c == ZERO || c == ONE || c == SEP)
         .copied()
@@ -91,45 +237,160 @@ pub fn decode_data(encoded: &str) -> Result<Vec<u8>> {
     
     for c in bit_chars {
         if c == SEP {
-            if current_bits.len() == 8 {
-                result.push(decode_byte(&current_bits)?);
+            if current_bits.len() != 8 {
+                return Err(WaterscapeError::Decoding(format!(
+                    "Incomplete byte after {} bytes ({} bits)",
+                    result.len(),
+                    current_bits.len()
+                )));
             }
+            result.push(decode_byte(&current_bits)?);
             current_bits.clear();
         } else {
             current_bits.push(c);
         }
     }
+    if !current_bits.is_empty() {
+        return Err(WaterscapeError::Decoding("Truncated zero-width payload".into()));
+    }
     
     Ok(result)
 }
 
+/// How encoded characters are spread over the cover text
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Placement {
+    /// Scatter characters over word boundaries with a keyed pseudo-random
+    /// distribution, so positions reveal nothing about the payload. Covers with
+    /// enough words get at most [`DEFAULT_MAX_PER_CHAR`] characters per word.
+    #[default]
+    Scattered,
+    /// Fill each position evenly and append whatever does not fit after the last one
+    Lenient,
+    /// Spread evenly with at most `max_per_char` encoded characters after any
+    /// visible character, failing with `CoverTextTooShort` instead of clustering
+    /// the remainder at the tail
+    Strict { max_per_char: usize },
+}
+
+impl Placement {
+    /// Strict placement with the default density
+    pub fn strict() -> Self {
+        Self::Strict {
+            max_per_char: DEFAULT_MAX_PER_CHAR,
+        }
+    }
+
+    /// Encoded characters allowed after one slot for an even layout
+    pub fn max_per_char(self) -> usize {
+        match self {
+            Self::Scattered => DEFAULT_MAX_PER_CHAR,
+            Self::Lenient => 1,
+            Self::Strict { max_per_char } => max_per_char.max(1),
+        }
+    }
+
+    /// What counts as a slot: a word end when scattering, any character otherwise
+    pub fn unit(self) -> &'static str {
+        match self {
+            Self::Scattered => "words",
+            Self::Lenient | Self::Strict { .. } => "chars",
+        }
+    }
+
+    /// Slots (see [`Placement::unit`]) needed to spread `encoded_len` characters evenly
+    pub fn min_cover_len(self, encoded_len: usize) -> usize {
+        encoded_len.div_ceil(self.max_per_char()).max(1)
+    }
+}
+
+/// Default density of [`Placement::strict`]
+pub const DEFAULT_MAX_PER_CHAR: usize = 4;
+
 /// Hide data within cover text by interleaving zero-width characters
 pub fn hide_in_text(cover_text: &str, data: &[u8]) -> Result<String> {
-    let encoded = encode_data(data);
+    hide_encoded_in_text(cover_text, &encode_data(data), Placement::default())
+}
+
+/// Hide data scattered over word boundaries, with placement driven by `key`
+///
+/// The receiver does not need the key: extraction ignores positions.
+pub fn hide_in_text_keyed(
+    cover_text: &str,
+    data: &[u8],
+    encoding: ZeroWidthEncoding,
+    key: &[u8; 32],
+) -> Result<String> {
+    scatter(cover_text, &encode_data_with(data, encoding), key, |_| true)
+}
+
+/// Hide data with an explicit encoding and placement
+pub fn hide_in_text_with(
+    cover_text: &str,
+    data: &[u8],
+    encoding: ZeroWidthEncoding,
+    placement: Placement,
+) -> Result<String> {
+    hide_encoded_in_text(cover_text, &encode_data_with(data, encoding), placement)
+}
+
+/// Interleave an already encoded zero-width string with cover text
+fn hide_encoded_in_text(cover_text: &str, encoded: &str, placement: Placement) -> Result<String> {
+    interleave(cover_text, encoded, placement, |_| true)
+}
+
+/// Distribute encoded characters after the cover characters accepted by `can_insert_after`
+fn interleave(
+    cover_text: &str,
+    encoded: &str,
+    placement: Placement,
+    can_insert_after: impl Fn(char) -> bool,
+) -> Result<String> {
+    if placement == Placement::Scattered {
+        return scatter(cover_text, encoded, &rand::random(), can_insert_after);
+    }
+
     let cover_chars: Vec<char> = cover_text.chars().collect();
     let encoded_chars: Vec<char> = encoded.chars().collect();
+    let slots = cover_chars.iter().filter(|&&c| can_insert_after(c)).count();
     
     // We need at least some cover text
-    if cover_chars.is_empty() {
+    if slots == 0 {
         return Err(WaterscapeError::CoverTextTooShort {
-            needed: 1,
+            needed: placement.min_cover_len(encoded_chars.len()),
             available: 0,
+            unit: "chars",
         });
     }
+    if let Placement::Strict { .. } = placement {
+        let needed = placement.min_cover_len(encoded_chars.len());
+        if slots < needed {
+            return Err(WaterscapeError::CoverTextTooShort {
+                needed,
+                available: slots,
+                unit: "chars",
+            });
+        }
+    }
     
     // Distribute encoded characters throughout the cover text
     let mut result = String::with_capacity(cover_text.len() + encoded.len());
-    let chunk_size = (encoded_chars.len() / cover_chars.len()).max(1);
+    let chunk_size = (encoded_chars.len() / slots).max(1);
     let mut encoded_idx = 0;
+    let mut slot = 0;
     
-    for (i, cover_char) in cover_chars.iter().enumerate() {
-        result.push(*cover_char);
+    for cover_char in cover_chars {
+        result.push(cover_char);
+        if !can_insert_after(cover_char) {
+            continue;
+        }
+        slot += 1;
         
         // Insert a chunk of encoded data after each cover character
-        let end_idx = if i == cover_chars.len() - 1 {
-            encoded_chars.len() // Put all remaining at the end
-        } else {
-            (encoded_idx + chunk_size).min(encoded_chars.len())
+        let end_idx = match placement {
+            Placement::Strict { .. } | Placement::Scattered => slot * encoded_chars.len() / slots,
+            Placement::Lenient if slot == slots => encoded_chars.len(), // Put all remaining at the end
+            Placement::Lenient => (encoded_idx + chunk_size).min(encoded_chars.len()),
         };
         
         for &enc_char in &encoded_chars[encoded_idx..end_idx] {
@@ -138,21 +399,94 @@ pub fn hide_in_text(cover_text: &str, data: &[u8]) -> Result<String> {
         encoded_idx = end_idx;
     }
     
-    // Append any remaining encoded characters
-    for &enc_char in &encoded_chars[encoded_idx..] {
-        result.push(enc_char);
+    Ok(result)
+}
+
+/// Scatter encoded characters over the word boundaries of the cover text
+///
+/// Every encoded character is assigned to a uniformly chosen boundary using a
+/// generator seeded with `key`, so the layout looks the same for every payload
+/// of a given size. Characters stay in order. When there are enough boundaries,
+/// a boundary stops taking characters once it holds the scattered density.
+fn scatter(
+    cover_text: &str,
+    encoded: &str,
+    key: &[u8; 32],
+    can_insert_after: impl Fn(char) -> bool,
+) -> Result<String> {
+    let cover_chars: Vec<char> = cover_text.chars().collect();
+    let insertable = |i: usize| can_insert_after(cover_chars[i]);
+    let word_ends: Vec<usize> = (0..cover_chars.len())
+        .filter(|&i| insertable(i) && !cover_chars[i].is_whitespace())
+        .filter(|&i| cover_chars.get(i + 1).is_none_or(|c| c.is_whitespace()))
+        .collect();
+    // Text without words (only whitespace) falls back to every position
+    let slots = if word_ends.is_empty() {
+        (0..cover_chars.len()).filter(|&i| insertable(i)).collect()
+    } else {
+        word_ends
+    };
+    let encoded_chars: Vec<char> = encoded.chars().collect();
+    if slots.is_empty() {
+        return Err(WaterscapeError::CoverTextTooShort {
+            needed: Placement::Scattered.min_cover_len(encoded_chars.len()),
+            available: 0,
+            unit: Placement::Scattered.unit(),
+        });
+    }
+
+    let mut rng = StdRng::from_seed(*key);
+    let max_per_slot = Placement::Scattered.max_per_char();
+    let capped = slots.len() * max_per_slot >= encoded_chars.len();
+    let mut counts = vec![0usize; slots.len()];
+    let mut open: Vec<usize> = (0..slots.len()).collect();
+    for _ in 0..encoded_chars.len() {
+        let pick = rng.gen_range(0..open.len());
+        counts[open[pick]] += 1;
+        if capped && counts[open[pick]] == max_per_slot {
+            open.swap_remove(pick);
+        }
+    }
+
+    let mut result = String::with_capacity(cover_text.len() + encoded.len());
+    let mut remaining = encoded_chars.iter();
+    let mut slot = 0;
+    for (i, &cover_char) in cover_chars.iter().enumerate() {
+        result.push(cover_char);
+        if slots.get(slot) == Some(&i) {
+            result.extend(remaining.by_ref().take(counts[slot]));
+            slot += 1;
+        }
     }
-    
     Ok(result)
 }
 
-/// Extract hidden data from text with interleaved zero-width characters
+/// Extract hidden data from text, using whichever carrier is present
 pub fn extract_from_text(text: &str) -> Result<Vec<u8>> {
+    if fec::has_hidden_data(text) {
+        return fec::extract_from_text(text);
+    }
+    if !(text.contains('\u{2060}') && text.contains('\u{FEFF}')) {
+        if tags::has_hidden_data(text) {
+            return tags::extract_from_text(text);
+        }
+        if variation::has_hidden_data(text) {
+            return variation::extract_from_text(text);
+        }
+        if whitespace::has_hidden_data(text) {
+            return whitespace::extract_from_text(text);
+        }
+        if homoglyph::has_hidden_data(text) {
+            return homoglyph::extract_from_text(text);
+        }
+    }
+    extract_zero_width(text)
+}
+
+/// Extract hidden data from text with interleaved zero-width characters
+fn extract_zero_width(text: &str) -> Result<Vec<u8>> {
     // Extract all zero-width characters including markers
-    let hidden: String = text
-        .chars()
-        .filter(|&c| c == ZERO || c == ONE || c == SEP || c == '\u{2060}' || c == '\u{FEFF}')
-        .collect();
+    let hidden: String = text.chars().filter(|&c| is_encoding_char(c)).collect();
     
     if hidden.is_empty() {
         return Err(WaterscapeError::NoHiddenMessage);
@@ -161,16 +495,130 @@ pub fn extract_from_text(text: &str) -> Result<Vec<u8>> {
     decode_data(&hidden)
 }
 
-/// Extract visible text (remove all zero-width characters)
+/// Extract visible text (remove all zero-width characters, tag, selector and whitespace
+/// payloads, and normalize homoglyphs)
 pub fn extract_visible_text(text: &str) -> String {
-    text.chars()
-        .filter(|&c| c != ZERO && c != ONE && c != SEP && c != '\u{2060}' && c != '\u{FEFF}')
-        .collect()
+    let visible: String = text.chars().filter(|&c| !is_encoding_char(c)).collect();
+    let visible = variation::extract_visible_text(&tags::extract_visible_text(&visible));
+    homoglyph::extract_visible_text(&whitespace::extract_visible_text(&visible))
 }
 
-/// Check if text contains hidden data
+/// Check if text contains hidden data in any supported carrier
 pub fn has_hidden_data(text: &str) -> bool {
-    text.contains('\u{2060}') && text.contains('\u{FEFF}')
+    (text.contains('\u{2060}') && text.contains('\u{FEFF}'))
+        || tags::has_hidden_data(text)
+        || variation::has_hidden_data(text)
+        || whitespace::has_hidden_data(text)
+        || homoglyph::has_hidden_data(text)
+}
+
+/// A carrier scheme that hides bytes in cover text
+///
+/// Implementations must be stateless with respect to individual messages so the
+/// same codec can be shared between channels and groups.
+pub trait StegoCodec: Send + Sync {
+    /// Short identifier of the scheme
+    fn name(&self) -> &'static str;
+
+    /// Hide data within cover text
+    fn hide(&self, cover_text: &str, data: &[u8]) -> Result<String>;
+
+    /// Hide data, using `key` for any pseudo-random choices the scheme makes
+    ///
+    /// Receivers never need the key. Schemes without such choices ignore it.
+    fn hide_keyed(&self, cover_text: &str, data: &[u8], _key: &[u8; 32]) -> Result<String> {
+        self.hide(cover_text, data)
+    }
+
+    /// Extract hidden data from text
+    fn extract(&self, text: &str) -> Result<Vec<u8>>;
+
+    /// Check if text appears to carry data in this scheme
+    fn detect(&self, text: &str) -> bool;
+
+    /// Maximum number of payload bytes the cover text can carry, `None` if unbounded
+    fn capacity(&self, cover_text: &str) -> Option<usize>;
+
+    /// Remove this scheme's hidden data, returning the visible text
+    fn strip(&self, text: &str) -> String;
+
+    /// Carrier size and minimum cover text for a payload of `len` bytes
+    fn requirement(&self, len: usize) -> CoverRequirement;
+}
+
+/// What a codec needs to embed a payload without clustering it
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub struct CoverRequirement {
+    /// Number of carrier symbols written (invisible characters, whitespace, letters)
+    pub encoded_len: usize,
+    /// Minimum amount of cover text for an even distribution
+    pub min_cover: usize,
+    /// Unit of `min_cover`: "chars", "words", "lines" or "letters"
+    pub unit: &'static str,
+}
+
+/// Zero-width character codec (the default carrier)
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ZeroWidthCodec {
+    pub encoding: ZeroWidthEncoding,
+    pub placement: Placement,
+}
+
+impl ZeroWidthCodec {
+    pub fn new(encoding: ZeroWidthEncoding) -> Self {
+        Self {
+            encoding,
+            placement: Placement::default(),
+        }
+    }
+
+    /// Use a different placement strategy
+    pub fn with_placement(mut self, placement: Placement) -> Self {
+        self.placement = placement;
+        self
+    }
+}
+
+impl StegoCodec for ZeroWidthCodec {
+    fn name(&self) -> &'static str {
+        "zero-width"
+    }
+
+    fn hide(&self, cover_text: &str, data: &[u8]) -> Result<String> {
+        hide_in_text_with(cover_text, data, self.encoding, self.placement)
+    }
+
+    fn hide_keyed(&self, cover_text: &str, data: &[u8], key: &[u8; 32]) -> Result<String> {
+        match self.placement {
+            Placement::Scattered => hide_in_text_keyed(cover_text, data, self.encoding, key),
+            _ => self.hide(cover_text, data),
+        }
+    }
+
+    fn extract(&self, text: &str) -> Result<Vec<u8>> {
+        extract_zero_width(text)
+    }
+
+    fn detect(&self, text: &str) -> bool {
+        text.contains('\u{2060}') && text.contains('\u{FEFF}')
+    }
+
+    fn capacity(&self, _cover_text: &str) -> Option<usize> {
+        None
+    }
+
+    fn strip(&self, text: &str) -> String {
+        text.chars().filter(|&c| !is_encoding_char(c)).collect()
+    }
+
+    fn requirement(&self, len: usize) -> CoverRequirement {
+        let encoded_len = self.encoding.encoded_len(len);
+        CoverRequirement {
+            encoded_len,
+            min_cover: self.placement.min_cover_len(encoded_len),
+            unit: self.placement.unit(),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -195,6 +643,129 @@ mod tests {
         assert_eq!(data.to_vec(), decoded);
     }
 
+    #[test]
+    fn test_dense_encodings() {
+        let data: Vec<u8> = (0..=255u8).collect();
+
+        for encoding in [ZeroWidthEncoding::Binary, ZeroWidthEncoding::Base4, ZeroWidthEncoding::Base8] {
+            let encoded = encode_data_with(&data, encoding);
+            assert_eq!(encoded.chars().count(), encoding.encoded_len(data.len()));
+            assert_eq!(decode_data(&encoded).unwrap(), data);
+        }
+
+        // Deprecated format characters are not part of any alphabet
+        assert!(BASE8_ALPHABET.iter().all(|c| !('\u{206A}'..='\u{206F}').contains(c)));
+
+        // Odd lengths exercise the padding bits of the 3-bit alphabet
+        for len in 0..8 {
+            let encoded = encode_data_with(&data[..len], ZeroWidthEncoding::Base8);
+            assert_eq!(decode_data(&encoded).unwrap(), data[..len].to_vec());
+        }
+    }
+
+    #[test]
+    fn test_dense_encoding_is_smaller() {
+        let data = [0xA5u8; 100];
+        let binary = encode_data_with(&data, ZeroWidthEncoding::Binary).chars().count();
+        let base8 = encode_data(&data).chars().count();
+        assert!(base8 * 3 < binary);
+    }
+
+    #[test]
+    fn test_legacy_encoding_still_decodes() {
+        // Appendix B.1 test vector: "Hi" in the original binary scheme
+        let legacy = "\u{2060}\u{200B}\u{200C}\u{200B}\u{200B}\u{200C}\u{200B}\u{200B}\u{200B}\u{200D}\
+                      \u{200B}\u{200C}\u{200C}\u{200B}\u{200C}\u{200B}\u{200B}\u{200C}\u{200D}\u{FEFF}";
+        assert_eq!(decode_data(legacy).unwrap(), b"Hi".to_vec());
+        assert_eq!(encode_data_with(b"Hi", ZeroWidthEncoding::Binary), legacy);
+
+        // A lost bit character is an error rather than a silently dropped byte
+        let damaged = legacy.replacen('\u{200C}', "", 1);
+        assert!(matches!(decode_data(&damaged), Err(WaterscapeError::Decoding(_))));
+    }
+
+    #[test]
+    fn test_cover_with_grapheme_joiner() {
+        // CGJ is ordinary text (it blocks reordering in Hebrew and German)
+        let cover = "Die Ku\u{034F}\u{0308}he und \u{05D0}\u{05B8}\u{034F}\u{05B7} sind hier.";
+        let data: Vec<u8> = (0..=255u8).collect();
+        let hidden = hide_in_text(cover, &data).unwrap();
+
+        assert_eq!(extract_from_text(&hidden).unwrap(), data);
+        assert_eq!(extract_visible_text(&hidden), cover);
+    }
+
+    #[test]
+    fn test_strict_placement() {
+        let data = [0x5Au8; 20];
+        let encoding = ZeroWidthEncoding::Base8;
+        let placement = Placement::Strict { max_per_char: 3 };
+        let needed = placement.min_cover_len(encoding.encoded_len(data.len()));
+
+        let cover = "y".repeat(needed);
+        let hidden = hide_in_text_with(&cover, &data, encoding, placement).unwrap();
+        assert_eq!(extract_from_text(&hidden).unwrap(), data.to_vec());
+
+        // No visible character is followed by more than three encoded characters
+        let longest_run = hidden.split('y').map(|run| run.chars().count()).max().unwrap();
+        assert!(longest_run <= 3);
+
+        match hide_in_text_with(&cover[1..], &data, encoding, placement) {
+            Err(WaterscapeError::CoverTextTooShort { needed: n, available, unit }) => {
+                assert_eq!((n, available, unit), (needed, needed - 1, "chars"));
+            }
+            other => panic!("Expected CoverTextTooShort, got {:?}", other.map(|_| ())),
+        }
+    }
+
+    #[test]
+    fn test_scattered_placement() {
+        let cover = "The quick brown fox jumps over the lazy dog and keeps running far away from here";
+        let data = [0xC3u8; 40];
+        let key = [7u8; 32];
+
+        let hidden = hide_in_text_keyed(cover, &data, ZeroWidthEncoding::Base8, &key).unwrap();
+        assert_eq!(hidden, hide_in_text_keyed(cover, &data, ZeroWidthEncoding::Base8, &key).unwrap());
+        assert_ne!(hidden, hide_in_text_keyed(cover, &data, ZeroWidthEncoding::Base8, &[8u8; 32]).unwrap());
+        assert_eq!(extract_from_text(&hidden).unwrap(), data.to_vec());
+        assert_eq!(extract_visible_text(&hidden), cover);
+
+        // Runs only sit at word ends, and none of them dominates
+        let encoded_len = ZeroWidthEncoding::Base8.encoded_len(data.len());
+        let words = cover.split(' ').count();
+        for (i, word) in hidden.split(' ').enumerate() {
+            let visible: String = word.chars().filter(|&c| !is_encoding_char(c)).collect();
+            assert!(word.starts_with(visible.as_str()), "payload inside word {}", i);
+            assert!(word.chars().count() - visible.chars().count() < encoded_len * 4 / words);
+        }
+    }
+
+    #[test]
+    fn test_scattered_min_cover() {
+        let data = [0x3Cu8; 24];
+        let codec = ZeroWidthCodec::new(ZeroWidthEncoding::Base8);
+        let requirement = codec.requirement(data.len());
+        assert_eq!(requirement.unit, "words");
+        let longest_run = |hidden: &str| {
+            hidden
+                .split(' ')
+                .map(|word| word.chars().filter(|&c| is_encoding_char(c)).count())
+                .max()
+                .unwrap()
+        };
+
+        // Exactly enough words keeps every run within the density
+        let cover = vec!["word"; requirement.min_cover].join(" ");
+        let hidden = codec.hide_keyed(&cover, &data, &[3u8; 32]).unwrap();
+        assert_eq!(extract_from_text(&hidden).unwrap(), data.to_vec());
+        assert!(longest_run(&hidden) <= DEFAULT_MAX_PER_CHAR);
+
+        // One word short forces some word past it
+        let short_cover = vec!["word"; requirement.min_cover - 1].join(" ");
+        let hidden = codec.hide_keyed(&short_cover, &data, &[3u8; 32]).unwrap();
+        assert!(longest_run(&hidden) > DEFAULT_MAX_PER_CHAR);
+    }
+
     #[test]
     fn test_hide_extract() {
         let cover = "This is a normal looking message.";
@@ -211,6 +782,38 @@ mod tests {
         assert_eq!(extracted, secret.to_vec());
     }
 
+    #[test]
+    fn test_zero_width_codec() {
+        let codec = ZeroWidthCodec::new(ZeroWidthEncoding::Base4);
+        let cover = "Codec cover text";
+
+        let hidden = codec.hide(cover, b"payload").unwrap();
+        assert!(codec.detect(&hidden));
+        assert!(!codec.detect(cover));
+        assert_eq!(codec.extract(&hidden).unwrap(), b"payload".to_vec());
+        assert_eq!(codec.strip(&hidden), cover);
+        assert_eq!(codec.capacity(cover), None);
+    }
+
+    #[test]
+    fn test_text_helpers_recognise_tags() {
+        let cover = "Tag carrier text";
+        let hidden = tags::hide_in_text(cover, b"tagged").unwrap();
+
+        assert!(has_hidden_data(&hidden));
+        assert_eq!(extract_visible_text(&hidden), cover);
+        assert_eq!(extract_from_text(&hidden).unwrap(), b"tagged".to_vec());
+    }
+
+    #[test]
+    fn test_text_helpers_recognise_selectors() {
+        let hidden = variation::hide_in_text("\u{1F525}", b"fire").unwrap();
+
+        assert!(has_hidden_data(&hidden));
+        assert_eq!(extract_visible_text(&hidden), "\u{1F525}");
+        assert_eq!(extract_from_text(&hidden).unwrap(), b"fire".to_vec());
+    }
+
     #[test]
     fn test_has_hidden_data() {
         let cover = "Normal text";
@@ -0,0 +1,358 @@
+//! Forward error correction for zero-width payloads
+//!
+//! The byte stream is split into `data_shards` shards and extended with
+//! `parity_shards` Reed-Solomon parity shards. Every shard is encoded as an
+//! independent zero-width frame (start marker to end marker), and the frames are
+//! spread over consecutive regions of the cover text. An edit or a stripped span
+//! only destroys the frames in that region; any `data_shards` intact frames are
+//! enough to recover the payload.
+//!
+//! ## Shard format
+//! ```text
+//! +-------+-------+--------+--------+-----------+-------------+----------+
+//! | Magic | Index | Data   | Parity | Length    | Shard bytes | Checksum |
+//! | 0xFE  | u8    | shards | shards | u16 (BE)  |             | 4 bytes  |
+//! +-------+-------+--------+--------+-----------+-------------+----------+
+//! ```
+//! The checksum is the first 4 bytes of SHA-256 over everything before it. Frames
+//! that fail the checksum are treated as erasures.
+
+use reed_solomon_erasure::galois_8::ReedSolomon;
+use sha2::{Digest, Sha256};
+
+use super::{
+    decode_data, encode_data_with, interleave, is_encoding_char, CoverRequirement, Placement, StegoCodec,
+    ZeroWidthEncoding,
+};
+use crate::error::WaterscapeError;
+use crate::Result;
+
+const MAGIC: u8 = 0xFE;
+const SHARD_HEADER_SIZE: usize = 6;
+const CHECKSUM_SIZE: usize = 4;
+const MAX_LEN: usize = u16::MAX as usize;
+
+/// Reed-Solomon shard counts
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FecParams {
+    /// Number of shards the payload is split into
+    pub data_shards: u8,
+    /// Number of parity shards; up to this many frames may be lost
+    pub parity_shards: u8,
+}
+
+impl Default for FecParams {
+    fn default() -> Self {
+        Self {
+            data_shards: 4,
+            parity_shards: 2,
+        }
+    }
+}
+
+impl FecParams {
+    pub fn new(data_shards: u8, parity_shards: u8) -> Self {
+        Self {
+            data_shards,
+            parity_shards,
+        }
+    }
+
+    /// Total number of frames written to the cover text
+    pub fn total_shards(&self) -> usize {
+        self.data_shards as usize + self.parity_shards as usize
+    }
+
+    /// Size of each shard's data for a payload of `len` bytes
+    pub fn shard_size(&self, len: usize) -> usize {
+        len.div_ceil(self.data_shards.max(1) as usize).max(1)
+    }
+
+    fn reed_solomon(&self) -> Result<ReedSolomon> {
+        ReedSolomon::new(self.data_shards as usize, self.parity_shards as usize)
+            .map_err(|e| WaterscapeError::Encoding(format!("Invalid FEC parameters: {:?}", e)))
+    }
+}
+
+fn checksum(bytes: &[u8]) -> [u8; CHECKSUM_SIZE] {
+    let digest = Sha256::digest(bytes);
+    let mut out = [0u8; CHECKSUM_SIZE];
+    out.copy_from_slice(&digest[..CHECKSUM_SIZE]);
+    out
+}
+
+/// A shard that passed its checksum
+struct Shard {
+    index: usize,
+    params: FecParams,
+    len: usize,
+    bytes: Vec<u8>,
+}
+
+impl Shard {
+    fn parse(frame: &[u8]) -> Option<Self> {
+        if frame.len() < SHARD_HEADER_SIZE + CHECKSUM_SIZE || frame[0] != MAGIC {
+            return None;
+        }
+        let (body, sum) = frame.split_at(frame.len() - CHECKSUM_SIZE);
+        if checksum(body) != sum {
+            return None;
+        }
+
+        let params = FecParams::new(body[2], body[3]);
+        let shard = Self {
+            index: body[1] as usize,
+            params,
+            len: u16::from_be_bytes([body[4], body[5]]) as usize,
+            bytes: body[SHARD_HEADER_SIZE..].to_vec(),
+        };
+        (shard.index < params.total_shards() && shard.bytes.len() == params.shard_size(shard.len)).then_some(shard)
+    }
+}
+
+/// Split data into checksummed data and parity shards
+pub fn encode_shards(data: &[u8], params: FecParams) -> Result<Vec<Vec<u8>>> {
+    if data.len() > MAX_LEN {
+        return Err(WaterscapeError::Encoding(format!(
+            "Payload too large for FEC ({} bytes, max {})",
+            data.len(),
+            MAX_LEN
+        )));
+    }
+
+    let rs = params.reed_solomon()?;
+    let shard_size = params.shard_size(data.len());
+    let mut shards: Vec<Vec<u8>> = (0..params.total_shards())
+        .map(|i| {
+            let start = (i * shard_size).min(data.len());
+            let end = ((i + 1) * shard_size).min(data.len());
+            let mut shard = data[start..end].to_vec();
+            shard.resize(shard_size, 0);
+            shard
+        })
+        .collect();
+    rs.encode(&mut shards)
+        .map_err(|e| WaterscapeError::Encoding(format!("FEC encoding failed: {:?}", e)))?;
+
+    let len = (data.len() as u16).to_be_bytes();
+    Ok(shards
+        .into_iter()
+        .enumerate()
+        .map(|(i, bytes)| {
+            let mut frame = vec![MAGIC, i as u8, params.data_shards, params.parity_shards, len[0], len[1]];
+            frame.extend(bytes);
+            let sum = checksum(&frame);
+            frame.extend(sum);
+            frame
+        })
+        .collect())
+}
+
+/// Recover data from whichever shards survived
+///
+/// Frames that are corrupted, truncated or not FEC shards at all are skipped.
+pub fn decode_shards<T: AsRef<[u8]>>(frames: &[T]) -> Result<Vec<u8>> {
+    let mut shards = frames.iter().filter_map(|frame| Shard::parse(frame.as_ref()));
+    let first = shards.next().ok_or(WaterscapeError::NoHiddenMessage)?;
+    let (params, len) = (first.params, first.len);
+
+    let mut slots: Vec<Option<Vec<u8>>> = vec![None; params.total_shards()];
+    for shard in std::iter::once(first).chain(shards) {
+        if shard.params == params && shard.len == len {
+            slots[shard.index] = Some(shard.bytes);
+        }
+    }
+
+    let intact = slots.iter().filter(|slot| slot.is_some()).count();
+    if intact < params.data_shards as usize {
+        return Err(WaterscapeError::Decoding(format!(
+            "Not enough intact FEC shards (have {}, need {})",
+            intact, params.data_shards
+        )));
+    }
+
+    params
+        .reed_solomon()
+        .map_err(|e| WaterscapeError::Decoding(e.to_string()))?
+        .reconstruct_data(&mut slots)
+        .map_err(|e| WaterscapeError::Decoding(format!("FEC reconstruction failed: {:?}", e)))?;
+
+    let mut data: Vec<u8> = slots
+        .into_iter()
+        .take(params.data_shards as usize)
+        .flat_map(|slot| slot.unwrap_or_default())
+        .collect();
+    data.truncate(len);
+    Ok(data)
+}
+
+/// Split the zero-width characters of a text into frames at each start marker
+fn frames(text: &str) -> Vec<String> {
+    let mut frames = Vec::new();
+    let mut current: Option<String> = None;
+    for c in text.chars().filter(|&c| is_encoding_char(c)) {
+        if c == '\u{2060}' {
+            frames.extend(current.take());
+            current = Some(String::new());
+        }
+        if let Some(frame) = current.as_mut() {
+            frame.push(c);
+            if c == '\u{FEFF}' {
+                frames.extend(current.take());
+            }
+        }
+    }
+    frames.extend(current);
+    frames
+}
+
+/// Decode every frame in the text that is still readable
+fn decoded_frames(text: &str) -> Vec<Vec<u8>> {
+    frames(text).iter().filter_map(|frame| decode_data(frame).ok()).collect()
+}
+
+/// Hide data with FEC, one zero-width frame per region of the cover text
+pub fn hide_in_text(cover_text: &str, data: &[u8], params: FecParams, encoding: ZeroWidthEncoding) -> Result<String> {
+    let shards = encode_shards(data, params)?;
+    let cover_chars: Vec<char> = cover_text.chars().collect();
+    if cover_chars.len() < shards.len() {
+        return Err(WaterscapeError::CoverTextTooShort {
+            needed: shards.len(),
+            available: cover_chars.len(),
+            unit: "chars",
+        });
+    }
+
+    let mut result = String::new();
+    for (i, shard) in shards.iter().enumerate() {
+        let start = i * cover_chars.len() / shards.len();
+        let end = (i + 1) * cover_chars.len() / shards.len();
+        let region: String = cover_chars[start..end].iter().collect();
+        result.push_str(&interleave(&region, &encode_data_with(shard, encoding), Placement::Lenient, |_| true)?);
+    }
+    Ok(result)
+}
+
+/// Extract FEC-protected data, repairing lost or damaged frames
+pub fn extract_from_text(text: &str) -> Result<Vec<u8>> {
+    decode_shards(&decoded_frames(text))
+}
+
+/// Check if text contains at least one intact FEC shard
+pub fn has_hidden_data(text: &str) -> bool {
+    decoded_frames(text).iter().any(|frame| Shard::parse(frame).is_some())
+}
+
+/// Zero-width codec with Reed-Solomon protection
+#[derive(Clone, Copy, Debug, Default)]
+pub struct FecCodec {
+    pub params: FecParams,
+    pub encoding: ZeroWidthEncoding,
+}
+
+impl FecCodec {
+    pub fn new(params: FecParams, encoding: ZeroWidthEncoding) -> Self {
+        Self { params, encoding }
+    }
+}
+
+impl StegoCodec for FecCodec {
+    fn name(&self) -> &'static str {
+        "zero-width-fec"
+    }
+
+    fn hide(&self, cover_text: &str, data: &[u8]) -> Result<String> {
+        hide_in_text(cover_text, data, self.params, self.encoding)
+    }
+
+    fn extract(&self, text: &str) -> Result<Vec<u8>> {
+        extract_from_text(text)
+    }
+
+    fn detect(&self, text: &str) -> bool {
+        has_hidden_data(text)
+    }
+
+    fn capacity(&self, _cover_text: &str) -> Option<usize> {
+        Some(MAX_LEN)
+    }
+
+    fn strip(&self, text: &str) -> String {
+        text.chars().filter(|&c| !is_encoding_char(c)).collect()
+    }
+
+    fn requirement(&self, len: usize) -> CoverRequirement {
+        let frame_len = SHARD_HEADER_SIZE + self.params.shard_size(len) + CHECKSUM_SIZE;
+        let encoded_len = self.params.total_shards() * self.encoding.encoded_len(frame_len);
+        CoverRequirement {
+            encoded_len,
+            min_cover: Placement::Lenient.min_cover_len(encoded_len),
+            unit: "chars",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const COVER: &str = "Moderators sometimes edit a post to fix a typo or remove a link, \
+                         and every character they touch takes hidden data with it.";
+
+    /// Remove every character in the char range, as an edit of that span would
+    fn cut(text: &str, start: usize, end: usize) -> String {
+        text.chars()
+            .enumerate()
+            .filter(|(i, _)| !(start..end).contains(i))
+            .map(|(_, c)| c)
+            .collect()
+    }
+
+    #[test]
+    fn test_shards_roundtrip() {
+        let data: Vec<u8> = (0..=200u8).collect();
+        let params = FecParams::new(4, 2);
+        let shards = encode_shards(&data, params).unwrap();
+        assert_eq!(shards.len(), 6);
+
+        // Any two shards may be lost or corrupted
+        let mut damaged = shards.clone();
+        damaged.remove(4);
+        damaged[0][SHARD_HEADER_SIZE] ^= 0xFF;
+        assert_eq!(decode_shards(&damaged).unwrap(), data);
+
+        damaged.remove(1);
+        assert!(matches!(decode_shards(&damaged), Err(WaterscapeError::Decoding(_))));
+    }
+
+    #[test]
+    fn test_survives_edit() {
+        let data = b"survives a moderator edit".to_vec();
+        let hidden = hide_in_text(COVER, &data, FecParams::default(), ZeroWidthEncoding::Base8).unwrap();
+        assert!(has_hidden_data(&hidden));
+
+        // Cut a span out of the middle of the text
+        let len = hidden.chars().count();
+        let edited = cut(&hidden, len / 3, len / 3 + len / 8);
+        assert_eq!(extract_from_text(&edited).unwrap(), data);
+        assert_eq!(super::super::extract_from_text(&edited).unwrap(), data);
+    }
+
+    #[test]
+    fn test_survives_truncation() {
+        let data = b"tail lost".to_vec();
+        let hidden = hide_in_text(COVER, &data, FecParams::new(3, 3), ZeroWidthEncoding::Binary).unwrap();
+
+        let len = hidden.chars().count();
+        let truncated = cut(&hidden, len * 6 / 10, len);
+        assert_eq!(extract_from_text(&truncated).unwrap(), data);
+    }
+
+    #[test]
+    fn test_cover_too_short() {
+        assert!(matches!(
+            hide_in_text("abc", b"x", FecParams::default(), ZeroWidthEncoding::Base8),
+            Err(WaterscapeError::CoverTextTooShort { needed: 6, available: 3, .. })
+        ));
+    }
+}
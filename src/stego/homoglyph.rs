@@ -0,0 +1,412 @@
+//! Homoglyph substitution carrier
+//!
+//! Encodes one bit per substitutable Latin letter: the letter itself is 0 and its
+//! visually identical Cyrillic or Greek counterpart is 1. No code points are added,
+//! so the carrier suits platforms that count or limit characters.
+//!
+//! Only words written in Latin script carry bits. A word containing any other
+//! letter, or no ASCII letter at all (a genuine Cyrillic or Greek word), is left
+//! exactly as it is.
+//!
+//! ## Encoding scheme
+//! Bits are read from the substitutable letters of the text in order, MSB first:
+//! ```text
+//! +-----------+-------------------+-----------------+-------------------+
+//! | Magic: 8  | Length: 16 bits   | Data: 8 bits    | Check: 16 bits    |
+//! | 0xB2      | byte count (BE)   | per byte        | SHA-256 prefix    |
+//! +-----------+-------------------+-----------------+-------------------+
+//! ```
+//! The check covers the header and data, so ordinary mixed-script text only
+//! passes for a payload with a 2^-24 chance. Letters after the payload keep
+//! their Latin form.
+
+use sha2::{Digest, Sha256};
+
+use super::{CoverRequirement, StegoCodec};
+use crate::error::WaterscapeError;
+use crate::Result;
+
+const MAGIC: u8 = 0xB2;
+const HEADER_BITS: usize = 24;
+const CHECK_BITS: usize = 16;
+const MAX_LEN: usize = u16::MAX as usize;
+
+/// Canonical mapping from Latin letters to their homoglyphs
+pub const HOMOGLYPHS: &[(char, char)] = &[
+    ('A', '\u{0410}'), // Cyrillic A
+    ('B', '\u{0412}'), // Cyrillic VE
+    ('C', '\u{0421}'), // Cyrillic ES
+    ('E', '\u{0415}'), // Cyrillic IE
+    ('H', '\u{041D}'), // Cyrillic EN
+    ('I', '\u{0406}'), // Cyrillic Byelorussian-Ukrainian I
+    ('J', '\u{0408}'), // Cyrillic JE
+    ('K', '\u{041A}'), // Cyrillic KA
+    ('M', '\u{041C}'), // Cyrillic EM
+    ('N', '\u{039D}'), // Greek NU
+    ('O', '\u{041E}'), // Cyrillic O
+    ('P', '\u{0420}'), // Cyrillic ER
+    ('S', '\u{0405}'), // Cyrillic DZE
+    ('T', '\u{0422}'), // Cyrillic TE
+    ('X', '\u{0425}'), // Cyrillic HA
+    ('Y', '\u{04AE}'), // Cyrillic straight U
+    ('Z', '\u{0396}'), // Greek ZETA
+    ('a', '\u{0430}'), // Cyrillic a
+    ('c', '\u{0441}'), // Cyrillic es
+    ('e', '\u{0435}'), // Cyrillic ie
+    ('i', '\u{0456}'), // Cyrillic Byelorussian-Ukrainian i
+    ('j', '\u{0458}'), // Cyrillic je
+    ('o', '\u{043E}'), // Cyrillic o
+    ('p', '\u{0440}'), // Cyrillic er
+    ('s', '\u{0455}'), // Cyrillic dze
+    ('x', '\u{0445}'), // Cyrillic ha
+    ('y', '\u{0443}'), // Cyrillic u
+];
+
+fn homoglyph_of(c: char) -> Option<char> {
+    HOMOGLYPHS.iter().find(|&&(latin, _)| latin == c).map(|&(_, glyph)| glyph)
+}
+
+pub(crate) fn latin_of(c: char) -> Option<char> {
+    HOMOGLYPHS.iter().find(|&&(_, glyph)| glyph == c).map(|&(latin, _)| latin)
+}
+
+/// Bit carried by a character, if it is a substitutable letter or a homoglyph
+fn bit_of(c: char) -> Option<bool> {
+    if homoglyph_of(c).is_some() {
+        Some(false)
+    } else if latin_of(c).is_some() {
+        Some(true)
+    } else {
+        None
+    }
+}
+
+/// Check whether a character is a homoglyph from the mapping table
+pub fn is_homoglyph(c: char) -> bool {
+    latin_of(c).is_some()
+}
+
+/// Part a character plays in the carrier
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Role {
+    /// Left as it is
+    Plain,
+    /// Always written in Latin so the word keeps an ASCII letter
+    Anchor,
+    /// Carries one bit
+    Carrier,
+}
+
+/// Assign each character its role
+///
+/// Only words made of Latin letters and our homoglyphs, with at least one
+/// ASCII letter, carry bits, so a Cyrillic word written entirely in lookalike
+/// letters ("сор") is left alone. A word whose every letter is substitutable
+/// keeps its first one in Latin as an anchor. Substitution never changes any of
+/// this, so sender and receiver agree on the carriers.
+fn roles(chars: &[char]) -> Vec<Role> {
+    let mut roles = vec![Role::Plain; chars.len()];
+    let mut i = 0;
+    while i < chars.len() {
+        if !chars[i].is_alphabetic() {
+            i += 1;
+            continue;
+        }
+        let start = i;
+        while i < chars.len() && chars[i].is_alphabetic() {
+            i += 1;
+        }
+        let word = &chars[start..i];
+        if !word.iter().all(|&c| c.is_ascii() || is_homoglyph(c)) || !word.iter().any(char::is_ascii) {
+            continue;
+        }
+        let mut anchored = word.iter().any(|&c| c.is_ascii() && bit_of(c).is_none());
+        for j in start..i {
+            if bit_of(chars[j]).is_none() {
+                continue;
+            }
+            roles[j] = if anchored { Role::Carrier } else { Role::Anchor };
+            anchored = true;
+        }
+    }
+    roles
+}
+
+/// Mark the characters that carry a bit
+fn carrier_mask(chars: &[char]) -> Vec<bool> {
+    roles(chars).into_iter().map(|role| role == Role::Carrier).collect()
+}
+
+/// Bits carried by the text, in order
+fn carrier_bits(text: &str) -> Vec<bool> {
+    let chars: Vec<char> = text.chars().collect();
+    let mask = carrier_mask(&chars);
+    chars
+        .into_iter()
+        .zip(mask)
+        .filter(|&(_, carrier)| carrier)
+        .filter_map(|(c, _)| bit_of(c))
+        .collect()
+}
+
+/// Number of substitutable letters (in either form) in Latin words of the text
+pub fn slot_count(text: &str) -> usize {
+    let chars: Vec<char> = text.chars().collect();
+    carrier_mask(&chars).into_iter().filter(|&carrier| carrier).count()
+}
+
+/// Number of letters needed to carry `len` bytes, including header and check
+pub fn encoded_len(len: usize) -> usize {
+    HEADER_BITS + len * 8 + CHECK_BITS
+}
+
+/// Maximum payload size the cover text can carry
+pub fn capacity(cover_text: &str) -> usize {
+    (slot_count(cover_text).saturating_sub(HEADER_BITS + CHECK_BITS) / 8).min(MAX_LEN)
+}
+
+/// Truncated SHA-256 over the header and data
+fn checksum(header: &[u8], data: &[u8]) -> [u8; CHECK_BITS / 8] {
+    let digest = Sha256::new().chain_update(header).chain_update(data).finalize();
+    [digest[0], digest[1]]
+}
+
+/// Read `count` bits from the carrier letters into bytes
+fn read_bytes(bits: &mut impl Iterator<Item = bool>, count: usize) -> Option<Vec<u8>> {
+    (0..count)
+        .map(|_| (0..8).try_fold(0u8, |byte, _| bits.next().map(|bit| (byte << 1) | bit as u8)))
+        .collect()
+}
+
+/// Read the payload, returning it only if the magic and check match
+fn read_payload(text: &str) -> Option<Vec<u8>> {
+    let mut bits = carrier_bits(text).into_iter();
+    let header = read_bytes(&mut bits, HEADER_BITS / 8)?;
+    if header[0] != MAGIC {
+        return None;
+    }
+    let len = u16::from_be_bytes([header[1], header[2]]) as usize;
+    let data = read_bytes(&mut bits, len)?;
+    let check = read_bytes(&mut bits, CHECK_BITS / 8)?;
+    (check == checksum(&header, &data)).then_some(data)
+}
+
+/// Hide data by substituting homoglyphs for Latin letters in the cover text
+pub fn hide_in_text(cover_text: &str, data: &[u8]) -> Result<String> {
+    if data.len() > MAX_LEN {
+        return Err(WaterscapeError::Encoding(format!(
+            "Payload too large for homoglyph carrier ({} bytes, max {})",
+            data.len(),
+            MAX_LEN
+        )));
+    }
+
+    let available = slot_count(cover_text);
+    let needed = encoded_len(data.len());
+    if available < needed {
+        return Err(WaterscapeError::CoverTextTooShort {
+            needed,
+            available,
+            unit: "letters",
+        });
+    }
+
+    let len = (data.len() as u16).to_be_bytes();
+    let header = [MAGIC, len[0], len[1]];
+    let check = checksum(&header, data);
+    let mut bits = header
+        .iter()
+        .chain(data)
+        .chain(&check)
+        .flat_map(|&byte| (0..8).rev().map(move |i| (byte >> i) & 1 == 1));
+
+    let chars: Vec<char> = cover_text.chars().collect();
+    let roles = roles(&chars);
+    Ok(chars
+        .into_iter()
+        .zip(roles)
+        .map(|(c, role)| {
+            match role {
+                Role::Plain => return c,
+                Role::Anchor => return latin_of(c).unwrap_or(c),
+                Role::Carrier => {}
+            }
+            let latin = latin_of(c).unwrap_or(c);
+            match bits.next() {
+                Some(true) => homoglyph_of(latin).unwrap_or(c),
+                Some(false) => latin,
+                None => c,
+            }
+        })
+        .collect())
+}
+
+/// Extract data hidden by homoglyph substitution
+pub fn extract_from_text(text: &str) -> Result<Vec<u8>> {
+    read_payload(text).ok_or(WaterscapeError::NoHiddenMessage)
+}
+
+/// Extract visible text (map the carrier letters back to Latin)
+///
+/// Only letters that are part of the payload are normalized, so genuine Cyrillic
+/// or Greek text after the payload is left alone.
+pub fn extract_visible_text(text: &str) -> String {
+    let Some(data) = read_payload(text) else {
+        return text.to_string();
+    };
+
+    let chars: Vec<char> = text.chars().collect();
+    let mask = carrier_mask(&chars);
+    let mut remaining = encoded_len(data.len());
+    chars
+        .into_iter()
+        .zip(mask)
+        .map(|(c, carrier)| {
+            if remaining == 0 || !carrier {
+                return c;
+            }
+            remaining -= 1;
+            latin_of(c).unwrap_or(c)
+        })
+        .collect()
+}
+
+/// Check if text carries a homoglyph payload
+pub fn has_hidden_data(text: &str) -> bool {
+    text.chars().any(is_homoglyph) && read_payload(text).is_some()
+}
+
+/// Homoglyph substitution codec
+#[derive(Clone, Copy, Debug, Default)]
+pub struct HomoglyphCodec;
+
+impl StegoCodec for HomoglyphCodec {
+    fn name(&self) -> &'static str {
+        "homoglyphs"
+    }
+
+    fn hide(&self, cover_text: &str, data: &[u8]) -> Result<String> {
+        hide_in_text(cover_text, data)
+    }
+
+    fn extract(&self, text: &str) -> Result<Vec<u8>> {
+        extract_from_text(text)
+    }
+
+    fn detect(&self, text: &str) -> bool {
+        has_hidden_data(text)
+    }
+
+    fn capacity(&self, cover_text: &str) -> Option<usize> {
+        Some(capacity(cover_text))
+    }
+
+    fn strip(&self, text: &str) -> String {
+        extract_visible_text(text)
+    }
+
+    fn requirement(&self, len: usize) -> CoverRequirement {
+        let bits = encoded_len(len);
+        CoverRequirement {
+            encoded_len: bits,
+            // One substitutable letter per bit
+            min_cover: bits,
+            unit: "letters",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const COVER: &str = "Some agents prefer to keep their posts short, others write long essays \
+                         about every topic that crosses their path. Either way is fine.";
+
+    #[test]
+    fn test_mapping_is_one_to_one() {
+        for (i, &(latin, glyph)) in HOMOGLYPHS.iter().enumerate() {
+            assert!(latin.is_ascii_alphabetic());
+            assert!(!glyph.is_ascii());
+            assert!(HOMOGLYPHS[i + 1..].iter().all(|&(l, g)| l != latin && g != glyph));
+        }
+    }
+
+    #[test]
+    fn test_hide_extract() {
+        let hidden = hide_in_text(COVER, b"hi").unwrap();
+
+        assert_eq!(hidden.chars().count(), COVER.chars().count());
+        assert_ne!(hidden, COVER);
+        assert!(has_hidden_data(&hidden));
+        assert!(!has_hidden_data(COVER));
+        assert_eq!(extract_from_text(&hidden).unwrap(), b"hi".to_vec());
+        assert_eq!(extract_visible_text(&hidden), COVER);
+    }
+
+    #[test]
+    fn test_capacity() {
+        let slots = slot_count(COVER);
+        assert_eq!(capacity(COVER), (slots - HEADER_BITS - CHECK_BITS) / 8);
+
+        let data = vec![0xFFu8; capacity(COVER) + 1];
+        match hide_in_text(COVER, &data) {
+            Err(WaterscapeError::CoverTextTooShort { needed, available, unit }) => {
+                assert_eq!((needed, available, unit), (encoded_len(data.len()), slots, "letters"));
+            }
+            other => panic!("Expected CoverTextTooShort, got {:?}", other.map(|_| ())),
+        }
+        assert!(hide_in_text(COVER, &data[1..]).is_ok());
+    }
+
+    #[test]
+    fn test_cyrillic_after_payload_preserved() {
+        let cover = format!("{} Привет", COVER);
+        let hidden = hide_in_text(&cover, b"x").unwrap();
+        assert_eq!(extract_visible_text(&hidden), cover);
+    }
+
+    #[test]
+    fn test_native_words_untouched() {
+        // Every mapped letter of the Cyrillic and Greek words would otherwise carry a 0
+        let cover = format!("Сергей прислал Ζεύς. {}", COVER);
+        let hidden = hide_in_text(&cover, &[0u8; 2]).unwrap();
+
+        assert!(hidden.starts_with("Сергей прислал Ζεύς. "));
+        assert_eq!(slot_count(&cover), slot_count(COVER));
+        assert_eq!(extract_from_text(&hidden).unwrap(), vec![0u8; 2]);
+        assert_eq!(extract_visible_text(&hidden), cover);
+    }
+
+    #[test]
+    fn test_lookalike_native_words_untouched() {
+        // Russian words spelled only with letters that have Latin twins
+        let cover = format!("сор ОСА {}", COVER);
+        let hidden = hide_in_text(&cover, &[0u8; 2]).unwrap();
+
+        assert!(hidden.starts_with("сор ОСА "));
+        assert_eq!(extract_from_text(&hidden).unwrap(), vec![0u8; 2]);
+        assert_eq!(extract_visible_text(&hidden), cover);
+    }
+
+    #[test]
+    fn test_corrupted_payload_not_detected() {
+        let hidden = hide_in_text(COVER, b"hi").unwrap();
+
+        // Flip the carrier of the first data bit: the magic still matches
+        let chars: Vec<char> = hidden.chars().collect();
+        let mask = carrier_mask(&chars);
+        let index = (0..chars.len()).filter(|&i| mask[i]).nth(HEADER_BITS).unwrap();
+        let flipped: String = chars
+            .iter()
+            .enumerate()
+            .map(|(i, &c)| match i == index {
+                true => homoglyph_of(c).or_else(|| latin_of(c)).unwrap(),
+                false => c,
+            })
+            .collect();
+
+        assert!(!has_hidden_data(&flipped));
+        assert!(matches!(extract_from_text(&flipped), Err(WaterscapeError::NoHiddenMessage)));
+    }
+}
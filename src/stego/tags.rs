@@ -0,0 +1,277 @@
+//! Unicode tag-character carrier
+//!
+//! Hides bytes in the Tags block (U+E0000–U+E007F), where every code point carries
+//! a full 7-bit value. Tag characters are invisible and survive several platforms
+//! and Markdown renderers that strip zero-width joiners.
+//!
+//! ## Encoding scheme
+//! ```text
+//! +----------------+------------------+---------------------------+
+//! | Start (U+E0001)| Length: 3 symbols| Data: 7-bit symbols, MSB  |
+//! | language tag   | 21-bit byte count| first, zero-padded        |
+//! +----------------+------------------+---------------------------+
+//! ```
+//!
+//! Tag characters that are not part of a payload (for example the tag sequences
+//! of subdivision flag emoji) are left untouched by extraction and stripping.
+
+use super::{interleave, CoverRequirement, Placement, StegoCodec};
+use crate::error::WaterscapeError;
+use crate::Result;
+
+const TAG_BASE: u32 = 0xE0000;
+const TAG_START: char = '\u{E0001}'; // Language tag - marks start of hidden data
+const TAG_CANCEL: char = '\u{E007F}'; // Cancel tag - terminates an emoji tag sequence
+const BLACK_FLAG: char = '\u{1F3F4}'; // Base of subdivision flag tag sequences
+const LENGTH_SYMBOLS: usize = 3;
+const MAX_LEN: usize = (1 << (7 * LENGTH_SYMBOLS)) - 1;
+
+fn is_tag_char(c: char) -> bool {
+    (TAG_BASE..=TAG_BASE + 0x7F).contains(&(c as u32))
+}
+
+fn symbol(value: u32) -> char {
+    char::from_u32(TAG_BASE + (value & 0x7F)).expect("tag block code points are valid chars")
+}
+
+/// Number of data symbols needed for `len` bytes
+fn data_symbols(len: usize) -> usize {
+    (len * 8).div_ceil(7)
+}
+
+/// Number of tag characters needed for `len` bytes, including start marker and length
+pub fn encoded_len(len: usize) -> usize {
+    1 + LENGTH_SYMBOLS + data_symbols(len)
+}
+
+/// Encode binary data as a tag-character string
+pub fn encode_data(data: &[u8]) -> Result<String> {
+    if data.len() > MAX_LEN {
+        return Err(WaterscapeError::Encoding(format!(
+            "Payload too large for tag carrier ({} bytes, max {})",
+            data.len(),
+            MAX_LEN
+        )));
+    }
+
+    let mut result = String::with_capacity(encoded_len(data.len()) * 4);
+    result.push(TAG_START);
+    let len = data.len() as u32;
+    for i in (0..LENGTH_SYMBOLS).rev() {
+        result.push(symbol(len >> (7 * i)));
+    }
+
+    let mut acc = 0u32;
+    let mut acc_bits = 0u32;
+    for &byte in data {
+        acc = (acc << 8) | byte as u32;
+        acc_bits += 8;
+        while acc_bits >= 7 {
+            acc_bits -= 7;
+            result.push(symbol(acc >> acc_bits));
+        }
+    }
+    if acc_bits > 0 {
+        result.push(symbol(acc << (7 - acc_bits)));
+    }
+    Ok(result)
+}
+
+/// Char indices of tag characters that are not part of an emoji flag sequence
+pub(crate) fn payload_tag_positions(chars: &[char]) -> Vec<usize> {
+    let mut positions = Vec::new();
+    let mut in_flag = false;
+    for (i, &c) in chars.iter().enumerate() {
+        if c == BLACK_FLAG {
+            in_flag = true;
+        } else if in_flag && is_tag_char(c) {
+            in_flag = c != TAG_CANCEL;
+        } else {
+            in_flag = false;
+            if is_tag_char(c) {
+                positions.push(i);
+            }
+        }
+    }
+    positions
+}
+
+/// Payload symbols are never inserted inside an emoji flag sequence
+fn can_insert_after(c: char) -> bool {
+    c != BLACK_FLAG && (!is_tag_char(c) || c == TAG_CANCEL)
+}
+
+/// Locate a payload: returns the char indices of the start marker and of all
+/// payload symbols, in order
+fn locate(chars: &[char]) -> Result<Vec<usize>> {
+    let candidates = payload_tag_positions(chars);
+    let start = candidates
+        .iter()
+        .position(|&i| chars[i] == TAG_START)
+        .ok_or(WaterscapeError::NoHiddenMessage)?;
+
+    let mut positions = vec![candidates[start]];
+    let mut tags = candidates[start + 1..].iter().copied();
+
+    let mut len = 0usize;
+    for _ in 0..LENGTH_SYMBOLS {
+        let i = tags
+            .next()
+            .ok_or_else(|| WaterscapeError::Decoding("Truncated tag length".into()))?;
+        len = (len << 7) | (chars[i] as u32 - TAG_BASE) as usize;
+        positions.push(i);
+    }
+
+    for _ in 0..data_symbols(len) {
+        let i = tags
+            .next()
+            .ok_or_else(|| WaterscapeError::Decoding("Truncated tag payload".into()))?;
+        positions.push(i);
+    }
+    Ok(positions)
+}
+
+/// Decode a tag-character payload from text
+pub fn decode_data(encoded: &str) -> Result<Vec<u8>> {
+    let chars: Vec<char> = encoded.chars().collect();
+    let positions = locate(&chars)?;
+
+    let len = positions[1..=LENGTH_SYMBOLS]
+        .iter()
+        .fold(0usize, |len, &i| (len << 7) | (chars[i] as u32 - TAG_BASE) as usize);
+
+    let mut result = Vec::with_capacity(len);
+    let mut acc = 0u32;
+    let mut acc_bits = 0u32;
+    for &i in &positions[1 + LENGTH_SYMBOLS..] {
+        acc = ((acc << 7) | (chars[i] as u32 - TAG_BASE)) & 0xFFFF;
+        acc_bits += 7;
+        if acc_bits >= 8 {
+            acc_bits -= 8;
+            result.push((acc >> acc_bits) as u8);
+        }
+    }
+    result.truncate(len);
+    Ok(result)
+}
+
+/// Hide data within cover text by interleaving tag characters
+pub fn hide_in_text(cover_text: &str, data: &[u8]) -> Result<String> {
+    interleave(cover_text, &encode_data(data)?, Placement::Lenient, can_insert_after)
+}
+
+/// Extract hidden data from text with interleaved tag characters
+pub fn extract_from_text(text: &str) -> Result<Vec<u8>> {
+    decode_data(text)
+}
+
+/// Extract visible text (remove the tag characters of a hidden payload)
+pub fn extract_visible_text(text: &str) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    match locate(&chars) {
+        Ok(positions) => chars
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| positions.binary_search(i).is_err())
+            .map(|(_, &c)| c)
+            .collect(),
+        // Incomplete payload: drop every payload tag from the marker on
+        Err(_) => {
+            let candidates = payload_tag_positions(&chars);
+            match candidates.iter().position(|&i| chars[i] == TAG_START) {
+                Some(start) => chars
+                    .iter()
+                    .enumerate()
+                    .filter(|(i, _)| candidates[start..].binary_search(i).is_err())
+                    .map(|(_, &c)| c)
+                    .collect(),
+                None => text.to_string(),
+            }
+        }
+    }
+}
+
+/// Check if text contains a tag-character payload
+pub fn has_hidden_data(text: &str) -> bool {
+    let chars: Vec<char> = text.chars().collect();
+    payload_tag_positions(&chars)
+        .iter()
+        .any(|&i| chars[i] == TAG_START)
+}
+
+/// Unicode tag-character codec
+#[derive(Clone, Copy, Debug, Default)]
+pub struct TagCodec;
+
+impl StegoCodec for TagCodec {
+    fn name(&self) -> &'static str {
+        "unicode-tags"
+    }
+
+    fn hide(&self, cover_text: &str, data: &[u8]) -> Result<String> {
+        hide_in_text(cover_text, data)
+    }
+
+    fn extract(&self, text: &str) -> Result<Vec<u8>> {
+        extract_from_text(text)
+    }
+
+    fn detect(&self, text: &str) -> bool {
+        has_hidden_data(text)
+    }
+
+    fn capacity(&self, _cover_text: &str) -> Option<usize> {
+        Some(MAX_LEN)
+    }
+
+    fn strip(&self, text: &str) -> String {
+        extract_visible_text(text)
+    }
+
+    fn requirement(&self, len: usize) -> CoverRequirement {
+        let encoded_len = encoded_len(len);
+        CoverRequirement {
+            encoded_len,
+            min_cover: Placement::Lenient.min_cover_len(encoded_len),
+            unit: "chars",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_decode_data() {
+        let data: Vec<u8> = (0..=255u8).collect();
+        for len in [0, 1, 6, 7, 8, 255, 256] {
+            let encoded = encode_data(&data[..len]).unwrap();
+            assert_eq!(encoded.chars().count(), encoded_len(len));
+            assert!(encoded.chars().all(is_tag_char));
+            assert_eq!(decode_data(&encoded).unwrap(), data[..len].to_vec());
+        }
+    }
+
+    #[test]
+    fn test_hide_extract() {
+        let cover = "Tags survive where joiners do not.";
+        let hidden = hide_in_text(cover, b"Secret payload!").unwrap();
+
+        assert_eq!(extract_visible_text(&hidden), cover);
+        assert_eq!(extract_from_text(&hidden).unwrap(), b"Secret payload!".to_vec());
+        assert!(has_hidden_data(&hidden));
+        assert!(!has_hidden_data(cover));
+    }
+
+    #[test]
+    fn test_flag_emoji_preserved() {
+        // Scotland flag: black flag + tag sequence "gbsct" + cancel tag
+        let flag = "\u{1F3F4}\u{E0067}\u{E0062}\u{E0073}\u{E0063}\u{E0074}\u{E007F}";
+        let cover = format!("Greetings from {} everyone", flag);
+
+        let hidden = hide_in_text(&cover, b"hi").unwrap();
+        assert_eq!(extract_visible_text(&hidden), cover);
+        assert_eq!(extract_from_text(&hidden).unwrap(), b"hi".to_vec());
+    }
+}
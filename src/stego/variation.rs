@@ -0,0 +1,282 @@
+//! Emoji variation-selector carrier
+//!
+//! Hides bytes in variation selectors attached to a single base character, one
+//! byte per invisible code point:
+//!
+//! - bytes 0–15 map to VS1–VS16 (U+FE00–U+FE0F)
+//! - bytes 16–255 map to VS17–VS256 (U+E0100–U+E01EF)
+//!
+//! The whole payload follows one visible character, so a single emoji is enough
+//! cover text.
+//!
+//! ## Encoding scheme
+//! ```text
+//! +--------------+---------------+----------------+----------------+
+//! | Base char    | Start (FVS1)  | Length: 2 VS   | Data: 1 VS per |
+//! | (+ own VS16) | U+180B        | u16 big endian | byte           |
+//! +--------------+---------------+----------------+----------------+
+//! ```
+//!
+//! All 256 standard selectors carry bytes, so the start marker is a Mongolian
+//! free variation selector that no byte maps to.
+//!
+//! If the base character already carries a variation selector (as in `❤️`),
+//! the payload is placed after it so the emoji presentation is preserved.
+
+use super::{CoverRequirement, StegoCodec};
+use crate::error::WaterscapeError;
+use crate::Result;
+
+const VS_START: char = '\u{180B}'; // Mongolian FVS1 - marks start of hidden data
+const MAX_LEN: usize = u16::MAX as usize;
+
+pub(crate) fn is_variation_selector(c: char) -> bool {
+    matches!(c, '\u{FE00}'..='\u{FE0F}' | '\u{E0100}'..='\u{E01EF}')
+}
+
+/// Any variation selector, including the Mongolian free variation selectors
+pub(crate) fn is_any_selector(c: char) -> bool {
+    is_variation_selector(c) || matches!(c, '\u{180B}'..='\u{180D}' | '\u{180F}')
+}
+
+fn byte_to_selector(byte: u8) -> char {
+    let cp = if byte < 16 {
+        0xFE00 + byte as u32
+    } else {
+        0xE0100 + (byte as u32 - 16)
+    };
+    char::from_u32(cp).expect("variation selector code points are valid chars")
+}
+
+fn selector_to_byte(c: char) -> Option<u8> {
+    match c {
+        '\u{FE00}'..='\u{FE0F}' => Some((c as u32 - 0xFE00) as u8),
+        '\u{E0100}'..='\u{E01EF}' => Some((c as u32 - 0xE0100 + 16) as u8),
+        _ => None,
+    }
+}
+
+/// Number of selectors needed for `len` bytes, including start marker and length
+pub fn encoded_len(len: usize) -> usize {
+    3 + len
+}
+
+/// Encode binary data as a string of variation selectors
+pub fn encode_data(data: &[u8]) -> Result<String> {
+    if data.len() > MAX_LEN {
+        return Err(WaterscapeError::Encoding(format!(
+            "Payload too large for variation selector carrier ({} bytes, max {})",
+            data.len(),
+            MAX_LEN
+        )));
+    }
+
+    let mut result = String::with_capacity(encoded_len(data.len()) * 4);
+    result.push(VS_START);
+    for &byte in (data.len() as u16).to_be_bytes().iter().chain(data) {
+        result.push(byte_to_selector(byte));
+    }
+    Ok(result)
+}
+
+/// Find the first start marker followed by a two-selector length header
+///
+/// FVS1 is an ordinary character in Mongolian text, so a marker without a
+/// header is not treated as the start of a payload.
+fn find_header(chars: &[char]) -> Option<(usize, usize)> {
+    (0..chars.len()).filter(|&i| chars[i] == VS_START).find_map(|start| {
+        let header = chars.get(start + 1..start + 3)?;
+        let high = selector_to_byte(header[0])?;
+        let low = selector_to_byte(header[1])?;
+        Some((start, u16::from_be_bytes([high, low]) as usize))
+    })
+}
+
+/// Locate a payload: returns the char range of the start marker, length and data
+fn locate(chars: &[char]) -> Result<std::ops::Range<usize>> {
+    let (start, len) = find_header(chars).ok_or(WaterscapeError::NoHiddenMessage)?;
+
+    let end = start + 3 + len;
+    if end > chars.len() || !chars[start + 3..end].iter().all(|&c| is_variation_selector(c)) {
+        return Err(WaterscapeError::Decoding("Truncated selector payload".into()));
+    }
+    Ok(start..end)
+}
+
+/// Decode a variation-selector payload from text
+pub fn decode_data(encoded: &str) -> Result<Vec<u8>> {
+    let chars: Vec<char> = encoded.chars().collect();
+    let range = locate(&chars)?;
+
+    Ok(chars[range.start + 3..range.end]
+        .iter()
+        .filter_map(|&c| selector_to_byte(c))
+        .collect())
+}
+
+/// Attach data to a single base character
+pub fn hide_on_char(base: char, data: &[u8]) -> Result<String> {
+    let mut result = String::from(base);
+    result.push_str(&encode_data(data)?);
+    Ok(result)
+}
+
+/// Hide data after the first character of the cover text
+pub fn hide_in_text(cover_text: &str, data: &[u8]) -> Result<String> {
+    let mut chars = cover_text.chars().peekable();
+    let base = chars.next().ok_or(WaterscapeError::CoverTextTooShort {
+        needed: 1,
+        available: 0,
+        unit: "chars",
+    })?;
+
+    let mut result = String::with_capacity(cover_text.len() + encoded_len(data.len()) * 4);
+    result.push(base);
+    // Keep the base character's own presentation selector in front of the payload
+    if let Some(&selector) = chars.peek().filter(|&&c| is_variation_selector(c)) {
+        result.push(selector);
+        chars.next();
+    }
+    result.push_str(&encode_data(data)?);
+    result.extend(chars);
+    Ok(result)
+}
+
+/// Extract hidden data from text
+pub fn extract_from_text(text: &str) -> Result<Vec<u8>> {
+    decode_data(text)
+}
+
+/// Extract visible text (remove the selectors of a hidden payload)
+pub fn extract_visible_text(text: &str) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let range = match locate(&chars) {
+        Ok(range) => range,
+        // Incomplete payload: drop the marker and the selector run following it
+        Err(_) => match find_header(&chars) {
+            Some((start, _)) => {
+                let run = chars[start + 1..]
+                    .iter()
+                    .take_while(|&&c| is_variation_selector(c))
+                    .count();
+                start..start + 1 + run
+            }
+            None => return text.to_string(),
+        },
+    };
+
+    chars[..range.start].iter().chain(&chars[range.end..]).collect()
+}
+
+/// Check if text contains a variation-selector payload
+pub fn has_hidden_data(text: &str) -> bool {
+    let chars: Vec<char> = text.chars().collect();
+    locate(&chars).is_ok()
+}
+
+/// Emoji variation-selector codec
+#[derive(Clone, Copy, Debug, Default)]
+pub struct VariationSelectorCodec;
+
+impl StegoCodec for VariationSelectorCodec {
+    fn name(&self) -> &'static str {
+        "variation-selectors"
+    }
+
+    fn hide(&self, cover_text: &str, data: &[u8]) -> Result<String> {
+        hide_in_text(cover_text, data)
+    }
+
+    fn extract(&self, text: &str) -> Result<Vec<u8>> {
+        extract_from_text(text)
+    }
+
+    fn detect(&self, text: &str) -> bool {
+        has_hidden_data(text)
+    }
+
+    fn capacity(&self, cover_text: &str) -> Option<usize> {
+        Some(if cover_text.is_empty() { 0 } else { MAX_LEN })
+    }
+
+    fn strip(&self, text: &str) -> String {
+        extract_visible_text(text)
+    }
+
+    fn requirement(&self, len: usize) -> CoverRequirement {
+        // The whole payload rides on a single base character
+        CoverRequirement {
+            encoded_len: encoded_len(len),
+            min_cover: 1,
+            unit: "chars",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_selector_mapping() {
+        for byte in 0..=255u8 {
+            let selector = byte_to_selector(byte);
+            assert!(is_variation_selector(selector));
+            assert_eq!(selector_to_byte(selector), Some(byte));
+        }
+    }
+
+    #[test]
+    fn test_hide_on_single_emoji() {
+        let data: Vec<u8> = (0..=255u8).collect();
+        let hidden = hide_on_char('\u{1F44D}', &data).unwrap();
+
+        assert_eq!(hidden.chars().count(), 1 + encoded_len(data.len()));
+        assert_eq!(extract_visible_text(&hidden), "\u{1F44D}");
+        assert_eq!(extract_from_text(&hidden).unwrap(), data);
+    }
+
+    #[test]
+    fn test_existing_selector_preserved() {
+        // Red heart with emoji presentation selector
+        let cover = "\u{2764}\u{FE0F} great thread";
+        let hidden = hide_in_text(cover, b"secret").unwrap();
+
+        assert!(hidden.starts_with("\u{2764}\u{FE0F}"));
+        assert!(has_hidden_data(&hidden));
+        assert_eq!(extract_visible_text(&hidden), cover);
+        assert_eq!(extract_from_text(&hidden).unwrap(), b"secret".to_vec());
+    }
+
+    #[test]
+    fn test_payload_with_marker_byte() {
+        // 255 bytes puts 0xFF (VS256) in the length as well as the data
+        let data = vec![0xFFu8; 255];
+        let hidden = hide_in_text("\u{1F525} lit", &data).unwrap();
+
+        assert!(!is_variation_selector(VS_START));
+        assert_eq!(hidden.matches(VS_START).count(), 1);
+        assert_eq!(extract_from_text(&hidden).unwrap(), data);
+        assert_eq!(extract_visible_text(&hidden), "\u{1F525} lit");
+
+        // Without the marker the 0xFF selectors are not mistaken for one
+        let unmarked = hidden.replace(VS_START, "");
+        assert!(!has_hidden_data(&unmarked));
+        assert!(matches!(extract_from_text(&unmarked), Err(WaterscapeError::NoHiddenMessage)));
+    }
+
+    #[test]
+    fn test_mongolian_text_untouched() {
+        // "Mongol" with a free variation selector on the first letter
+        let mongolian = "\u{182E}\u{180B}\u{1823}\u{1829}\u{182D}\u{1823}\u{182F}";
+        assert!(!has_hidden_data(mongolian));
+        assert!(!crate::stego::has_hidden_data(mongolian));
+        assert_eq!(extract_visible_text(mongolian), mongolian);
+        assert!(matches!(extract_from_text(mongolian), Err(WaterscapeError::NoHiddenMessage)));
+
+        // A payload on the first letter leaves the later FVS1 alone
+        let hidden = hide_in_text(&format!("\u{1820} {}", mongolian), b"hi").unwrap();
+        assert_eq!(extract_from_text(&hidden).unwrap(), b"hi".to_vec());
+        assert_eq!(extract_visible_text(&hidden), format!("\u{1820} {}", mongolian));
+    }
+}
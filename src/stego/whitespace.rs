@@ -0,0 +1,265 @@
+//! Trailing-whitespace carrier (SNOW-style)
+//!
+//! Encodes bits as spaces (0) and tabs (1) appended to the end of each line of the
+//! cover text. Only ASCII is used, so the carrier works in plain-text logs, issue
+//! trackers and code blocks that strip every non-ASCII invisible character.
+//!
+//! ## Encoding scheme
+//! The payload is split into equal chunks of `bytes_per_line` bytes, one chunk per
+//! line. The first line additionally ends with a 32-bit header:
+//! ```text
+//! line 0:  <text><original ws><chunk 0 bits><magic:8><length:16><bytes_per_line:8>
+//! line n:  <text><original ws><chunk n bits>
+//! ```
+//! Bits are written MSB first. Line endings (`\n` or `\r\n`) are preserved and
+//! whitespace already present at the end of a line is kept in front of the payload.
+
+use super::{CoverRequirement, StegoCodec};
+use crate::error::WaterscapeError;
+use crate::Result;
+
+const ZERO: char = ' ';
+const ONE: char = '\t';
+const MAGIC: u8 = 0xA5;
+const HEADER_BITS: usize = 32;
+const MAX_BYTES_PER_LINE: usize = u8::MAX as usize;
+const MAX_LEN: usize = u16::MAX as usize;
+
+struct Header {
+    len: usize,
+    bytes_per_line: usize,
+}
+
+/// Split text into lines, separating a trailing `\r` from each line body
+fn split_lines(text: &str) -> Vec<(&str, &str)> {
+    text.split('\n')
+        .map(|line| match line.strip_suffix('\r') {
+            Some(body) => (body, "\r"),
+            None => (line, ""),
+        })
+        .collect()
+}
+
+fn push_bits(out: &mut String, bytes: &[u8]) {
+    for &byte in bytes {
+        for i in (0..8).rev() {
+            out.push(if (byte >> i) & 1 == 1 { ONE } else { ZERO });
+        }
+    }
+}
+
+fn read_bits(symbols: &[char]) -> Option<Vec<u8>> {
+    symbols
+        .chunks(8)
+        .map(|chunk| {
+            chunk.iter().try_fold(0u8, |byte, &c| match c {
+                ZERO => Some(byte << 1),
+                ONE => Some((byte << 1) | 1),
+                _ => None,
+            })
+        })
+        .collect()
+}
+
+/// Last `count` chars of a line body, if it is that long
+fn suffix(body: &str, count: usize) -> Option<Vec<char>> {
+    let chars: Vec<char> = body.chars().collect();
+    chars.len().checked_sub(count).map(|start| chars[start..].to_vec())
+}
+
+/// Number of lines in the cover text
+pub fn line_count(cover_text: &str) -> usize {
+    cover_text.split('\n').count()
+}
+
+/// Maximum payload size the cover text can carry
+pub fn capacity(cover_text: &str) -> usize {
+    (line_count(cover_text) * MAX_BYTES_PER_LINE).min(MAX_LEN)
+}
+
+/// Read the header and compute the number of encoded chars at the end of each line
+fn locate(text: &str) -> Result<(Header, Vec<usize>)> {
+    let lines = split_lines(text);
+    let header_symbols = suffix(lines[0].0, HEADER_BITS).ok_or(WaterscapeError::NoHiddenMessage)?;
+    let header = read_bits(&header_symbols).ok_or(WaterscapeError::NoHiddenMessage)?;
+    if header[0] != MAGIC || header[3] == 0 {
+        return Err(WaterscapeError::NoHiddenMessage);
+    }
+
+    let header = Header {
+        len: u16::from_be_bytes([header[1], header[2]]) as usize,
+        bytes_per_line: header[3] as usize,
+    };
+
+    let mut suffix_lens = Vec::with_capacity(lines.len());
+    let mut remaining = header.len;
+    for i in 0..lines.len() {
+        let chunk = remaining.min(header.bytes_per_line);
+        remaining -= chunk;
+        suffix_lens.push(chunk * 8 + if i == 0 { HEADER_BITS } else { 0 });
+    }
+    if remaining > 0 {
+        return Err(WaterscapeError::Decoding("Truncated whitespace payload: missing lines".into()));
+    }
+    Ok((header, suffix_lens))
+}
+
+/// Hide data in the trailing whitespace of each line of the cover text
+pub fn hide_in_text(cover_text: &str, data: &[u8]) -> Result<String> {
+    if data.len() > MAX_LEN {
+        return Err(WaterscapeError::Encoding(format!(
+            "Payload too large for whitespace carrier ({} bytes, max {})",
+            data.len(),
+            MAX_LEN
+        )));
+    }
+
+    let lines = split_lines(cover_text);
+    let bytes_per_line = data.len().div_ceil(lines.len()).max(1);
+    if bytes_per_line > MAX_BYTES_PER_LINE {
+        return Err(WaterscapeError::CoverTextTooShort {
+            needed: data.len().div_ceil(MAX_BYTES_PER_LINE),
+            available: lines.len(),
+            unit: "lines",
+        });
+    }
+
+    let mut result = String::with_capacity(cover_text.len() + data.len() * 8 + HEADER_BITS);
+    let mut chunks = data.chunks(bytes_per_line);
+    for (i, (body, line_end)) in lines.iter().enumerate() {
+        if i > 0 {
+            result.push('\n');
+        }
+        result.push_str(body);
+        push_bits(&mut result, chunks.next().unwrap_or_default());
+        if i == 0 {
+            let len = (data.len() as u16).to_be_bytes();
+            push_bits(&mut result, &[MAGIC, len[0], len[1], bytes_per_line as u8]);
+        }
+        result.push_str(line_end);
+    }
+    Ok(result)
+}
+
+/// Extract hidden data from trailing whitespace
+pub fn extract_from_text(text: &str) -> Result<Vec<u8>> {
+    let (header, suffix_lens) = locate(text)?;
+    let lines = split_lines(text);
+
+    let mut data = Vec::with_capacity(header.len);
+    for (i, (&(body, _), &suffix_len)) in lines.iter().zip(&suffix_lens).enumerate() {
+        let data_len = suffix_len - if i == 0 { HEADER_BITS } else { 0 };
+        let symbols = suffix(body, suffix_len)
+            .ok_or_else(|| WaterscapeError::Decoding("Truncated whitespace payload".into()))?;
+        let bytes = read_bits(&symbols[..data_len])
+            .ok_or_else(|| WaterscapeError::Decoding("Trailing whitespace was altered".into()))?;
+        data.extend(bytes);
+    }
+    Ok(data)
+}
+
+/// Extract visible text (remove only the encoded trailing whitespace)
+pub fn extract_visible_text(text: &str) -> String {
+    let suffix_lens = match locate(text) {
+        Ok((_, suffix_lens)) => suffix_lens,
+        Err(_) => return text.to_string(),
+    };
+
+    split_lines(text)
+        .iter()
+        .zip(&suffix_lens)
+        .map(|(&(body, line_end), &suffix_len)| {
+            let keep = body.chars().count().saturating_sub(suffix_len);
+            let mut line: String = body.chars().take(keep).collect();
+            line.push_str(line_end);
+            line
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Check if text carries a whitespace payload
+pub fn has_hidden_data(text: &str) -> bool {
+    locate(text).is_ok()
+}
+
+/// Trailing-whitespace codec
+#[derive(Clone, Copy, Debug, Default)]
+pub struct WhitespaceCodec;
+
+impl StegoCodec for WhitespaceCodec {
+    fn name(&self) -> &'static str {
+        "trailing-whitespace"
+    }
+
+    fn hide(&self, cover_text: &str, data: &[u8]) -> Result<String> {
+        hide_in_text(cover_text, data)
+    }
+
+    fn extract(&self, text: &str) -> Result<Vec<u8>> {
+        extract_from_text(text)
+    }
+
+    fn detect(&self, text: &str) -> bool {
+        has_hidden_data(text)
+    }
+
+    fn capacity(&self, cover_text: &str) -> Option<usize> {
+        Some(capacity(cover_text))
+    }
+
+    fn strip(&self, text: &str) -> String {
+        extract_visible_text(text)
+    }
+
+    fn requirement(&self, len: usize) -> CoverRequirement {
+        // Each line end carries at most MAX_BYTES_PER_LINE bytes of trailing whitespace
+        CoverRequirement {
+            encoded_len: len * 8 + HEADER_BITS,
+            min_cover: len.div_ceil(MAX_BYTES_PER_LINE).max(1),
+            unit: "lines",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const COVER: &str = "fn main() {  \r\n    println!(\"hello\");\n}\n";
+
+    #[test]
+    fn test_hide_extract() {
+        let data: Vec<u8> = (0..40u8).collect();
+        let hidden = hide_in_text(COVER, &data).unwrap();
+
+        assert!(hidden.is_ascii());
+        assert!(has_hidden_data(&hidden));
+        assert!(!has_hidden_data(COVER));
+        assert_eq!(extract_from_text(&hidden).unwrap(), data);
+        // Original trailing whitespace and CRLF line endings survive
+        assert_eq!(extract_visible_text(&hidden), COVER);
+    }
+
+    #[test]
+    fn test_capacity_by_lines() {
+        assert_eq!(line_count(COVER), 4);
+        assert_eq!(capacity(COVER), 4 * 255);
+
+        let data = vec![0u8; 4 * 255 + 1];
+        match hide_in_text(COVER, &data) {
+            Err(WaterscapeError::CoverTextTooShort { needed, available, unit }) => {
+                assert_eq!((needed, available, unit), (5, 4, "lines"));
+            }
+            other => panic!("Expected CoverTextTooShort, got {:?}", other.map(|_| ())),
+        }
+        assert!(hide_in_text(COVER, &data[1..]).is_ok());
+    }
+
+    #[test]
+    fn test_single_line_cover() {
+        let hidden = hide_in_text("one line only", b"abc").unwrap();
+        assert_eq!(extract_from_text(&hidden).unwrap(), b"abc".to_vec());
+        assert_eq!(extract_visible_text(&hidden), "one line only");
+    }
+}
@@ -6,9 +6,9 @@
 use wasm_bindgen::prelude::*;
 
 #[cfg(feature = "wasm")]
-use crate::agent::Agent;
+use crate::agent::{Agent, AgentBackup};
 #[cfg(feature = "wasm")]
-use crate::protocol::{Waterscape, WaterscapeGroup};
+use crate::protocol::{GroupKeyEnvelope, GroupRole, Waterscape, WaterscapeGroup};
 
 /// JavaScript-friendly Agent wrapper
 #[cfg(feature = "wasm")]
@@ -52,6 +52,26 @@ impl WasmAgent {
     pub fn export_signing_key(&self) -> Vec<u8> {
         self.inner.export_signing_key().to_vec()
     }
+
+    /// Export name and both private keys as backup JSON
+    #[wasm_bindgen(js_name = exportBackupJson)]
+    pub fn export_backup_json(&self) -> Result<String, JsValue> {
+        let bytes = self
+            .inner
+            .export_backup()
+            .to_bytes()
+            .map_err(|e| JsValue::from_str(&e.to_string()))?;
+        String::from_utf8(bytes).map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
+    /// Restore an agent from backup JSON
+    #[wasm_bindgen(js_name = fromBackupJson)]
+    pub fn from_backup_json(backup_json: &str) -> Result<WasmAgent, JsValue> {
+        let backup = AgentBackup::from_bytes(backup_json.as_bytes())
+            .map_err(|e| JsValue::from_str(&e.to_string()))?;
+        let inner = Agent::from_backup(&backup).map_err(|e| JsValue::from_str(&e.to_string()))?;
+        Ok(Self { inner })
+    }
 }
 
 /// JavaScript-friendly Waterscape API
@@ -147,6 +167,86 @@ impl WasmWaterscapeGroup {
         })
     }
 
+    /// Join a group from the key envelope a member issued for this agent
+    #[wasm_bindgen(js_name = fromEnvelope)]
+    pub fn from_envelope(member: &WasmAgent, envelope_json: &str) -> Result<WasmWaterscapeGroup, JsValue> {
+        let envelope = GroupKeyEnvelope::from_bytes(envelope_json.as_bytes())
+            .map_err(|e| JsValue::from_str(&format!("Invalid envelope JSON: {}", e)))?;
+
+        Ok(Self {
+            inner: WaterscapeGroup::from_envelope(&member.inner, &envelope)
+                .map_err(|e| JsValue::from_str(&e.to_string()))?,
+        })
+    }
+
+    /// Key envelopes for every other member, as a JSON array
+    #[wasm_bindgen]
+    pub fn envelopes(&self, sender: &WasmAgent) -> Result<String, JsValue> {
+        let envelopes = self.inner
+            .envelopes(&sender.inner)
+            .map_err(|e| JsValue::from_str(&e.to_string()))?;
+        serde_json::to_string(&envelopes).map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
+    /// Apply a re-key envelope from a current admin
+    #[wasm_bindgen(js_name = applyEnvelope)]
+    pub fn apply_envelope(&mut self, member: &WasmAgent, envelope_json: &str) -> Result<(), JsValue> {
+        let envelope = GroupKeyEnvelope::from_bytes(envelope_json.as_bytes())
+            .map_err(|e| JsValue::from_str(&format!("Invalid envelope JSON: {}", e)))?;
+        self.inner
+            .apply_envelope(&member.inner, &envelope)
+            .map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
+    /// Add a member with a role (`admin`, `member` or `read_only`) and re-key;
+    /// returns the new envelopes as a JSON array
+    #[wasm_bindgen(js_name = addMember)]
+    pub fn add_member(&mut self, sender: &WasmAgent, identity_json: &str, role: &str) -> Result<String, JsValue> {
+        let member: crate::agent::PublicIdentity = serde_json::from_str(identity_json)
+            .map_err(|e| JsValue::from_str(&format!("Invalid identity JSON: {}", e)))?;
+        let envelopes = self.inner
+            .add_member(&sender.inner, member, parse_role(role)?)
+            .map_err(|e| JsValue::from_str(&e.to_string()))?;
+        serde_json::to_string(&envelopes).map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
+    /// Remove a member by hex signing key and re-key; returns the new envelopes as a JSON array
+    #[wasm_bindgen(js_name = removeMember)]
+    pub fn remove_member(&mut self, sender: &WasmAgent, signing_key_hex: &str) -> Result<String, JsValue> {
+        let mut signing_key = [0u8; 32];
+        hex::decode_to_slice(signing_key_hex, &mut signing_key)
+            .map_err(|e| JsValue::from_str(&format!("Invalid signing key: {}", e)))?;
+        let envelopes = self.inner
+            .remove_member(&sender.inner, &signing_key)
+            .map_err(|e| JsValue::from_str(&e.to_string()))?;
+        serde_json::to_string(&envelopes).map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
+    /// Change a member's role by hex signing key and re-key; returns the new
+    /// envelopes as a JSON array
+    #[wasm_bindgen(js_name = setRole)]
+    pub fn set_role(&mut self, sender: &WasmAgent, signing_key_hex: &str, role: &str) -> Result<String, JsValue> {
+        let mut signing_key = [0u8; 32];
+        hex::decode_to_slice(signing_key_hex, &mut signing_key)
+            .map_err(|e| JsValue::from_str(&format!("Invalid signing key: {}", e)))?;
+        let envelopes = self.inner
+            .set_role(&sender.inner, &signing_key, parse_role(role)?)
+            .map_err(|e| JsValue::from_str(&e.to_string()))?;
+        serde_json::to_string(&envelopes).map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
+    /// Current members and their roles, as a JSON array
+    #[wasm_bindgen]
+    pub fn members(&self) -> Result<String, JsValue> {
+        serde_json::to_string(self.inner.members()).map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
+    /// Current key epoch
+    #[wasm_bindgen(getter)]
+    pub fn epoch(&self) -> u32 {
+        self.inner.epoch()
+    }
+
     /// Get the group name
     #[wasm_bindgen(getter)]
     pub fn name(&self) -> String {
@@ -173,6 +273,21 @@ impl WasmWaterscapeGroup {
             .decode(text)
             .map_err(|e| JsValue::from_str(&e.to_string()))
     }
+
+    /// Decode a group message with its sender and cover status, as JSON
+    #[wasm_bindgen(js_name = decodeVerified)]
+    pub fn decode_verified(&self, text: &str) -> Result<String, JsValue> {
+        let decoded = self.inner
+            .decode_verified(text)
+            .map_err(|e| JsValue::from_str(&e.to_string()))?;
+        serde_json::to_string(&decoded).map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+}
+
+#[cfg(feature = "wasm")]
+fn parse_role(role: &str) -> Result<GroupRole, JsValue> {
+    serde_json::from_value(serde_json::Value::String(role.to_string()))
+        .map_err(|_| JsValue::from_str(&format!("Unknown group role: {}", role)))
 }
 
 /// Initialize panic hook for better error messages in WASM